@@ -0,0 +1,419 @@
+use crate::config::DEFAULT_DICTIONARY;
+use crate::daemon::default_socket_path;
+use crate::server::DEFAULT_MAX_BODY_SIZE;
+use crate::spell_check::autofix::DEFAULT_CONFIDENCE;
+use crate::spell_check::precomputed_levenshtein_checker::DEFAULT_GRAPH_DISTANCE;
+use crate::utils::audit_dictionary::DEFAULT_FREQUENCY_RATIO;
+use crate::utils::io::OutputFormat;
+use crate::utils::tokenizer::TOKENIZER_NAMES;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_INDEX: &str = "data/index/neighbors.tsv";
+
+#[derive(Parser)]
+#[command(
+    name = "rust-spell-checker",
+    version,
+    about = "Spell checker for prose, markup and source code"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check files and report every misspelled word with its suggestions
+    Check(CheckArgs),
+    /// Check only the lines a unified diff adds, such as `git diff` output
+    Diff(DiffArgs),
+    /// Apply confident corrections, as a unified diff or in place
+    Fix(FixArgs),
+    /// Walk through each misspelling, choosing how to correct it
+    Interactive(InteractiveArgs),
+    /// Answer the `ispell -a` pipe protocol on stdin, for editors
+    Pipe(EditorArgs),
+    /// Run a language server on stdio, publishing diagnostics to editors
+    Lsp(EditorArgs),
+    /// Serve checks over HTTP, with LanguageTool-compatible responses
+    Serve(ServeArgs),
+    /// Keep a checker loaded in the background for `check` and `suggest`
+    Daemon(DaemonArgs),
+    /// Suggest corrections for a single word
+    Suggest(SuggestArgs),
+    /// Time every checker on a dataset and compare their results
+    Bench(BenchArgs),
+    /// Precompute the neighbor graph used by the neighbor-graph algorithm
+    BuildIndex(BuildIndexArgs),
+    /// Inspect and maintain dictionaries
+    #[command(subcommand)]
+    Dict(DictCommand),
+    /// Print the effective configuration for a file or directory
+    Config(ConfigCommandArgs),
+}
+
+#[derive(Subcommand)]
+pub enum DictCommand {
+    /// Report suspicious dictionary entries
+    Audit(AuditArgs),
+    /// List the dictionary words linked to a word in the neighbor graph
+    Similar(SimilarArgs),
+}
+
+/// Spell checking algorithm used to suggest corrections.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    /// Exact lookup only, no suggestions
+    Hashmap,
+    Levenshtein,
+    WagnerFischer,
+    /// Precomputed graph from `build-index`, the fastest for large dictionaries
+    NeighborGraph,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Hashmap => "hashmap",
+            Algorithm::Levenshtein => "levenshtein",
+            Algorithm::WagnerFischer => "wagner_fischer",
+            Algorithm::NeighborGraph => "neighbor_graph",
+        }
+    }
+}
+
+/// What to do with words typed without the accents of their dictionary form.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccentMode {
+    /// Accept "cafe" when the dictionary has "café"
+    Accept,
+    /// Report "cafe", suggesting "café" first
+    Suggest,
+}
+
+#[derive(Args)]
+pub struct DictionaryArgs {
+    /// Word list with one word per line; repeat to combine several. Replaces the
+    /// dictionaries of the configuration, `data/dictionary/dict.txt` if it has none
+    #[arg(short, long = "dictionary", value_name = "FILE")]
+    pub dictionaries: Vec<String>,
+
+    /// Word list that words added interactively go to [default: .spellcheck-words.txt]
+    #[arg(long, value_name = "FILE")]
+    pub personal: Option<String>,
+
+    /// Neighbor graph built with `build-index`
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_INDEX)]
+    pub index: String,
+}
+
+// Options left unset fall back to the configuration files, then to the built-in defaults
+#[derive(Args)]
+pub struct CheckerArgs {
+    /// Largest edit distance of a suggestion [default: 2]
+    #[arg(long)]
+    pub distance: Option<usize>,
+
+    /// Number of suggestions per word [default: 3]
+    #[arg(short = 'k', long)]
+    pub top_k: Option<usize>,
+
+    /// How words are split out of the text [default: regex]
+    #[arg(long, env = "SPELL_CHECK_TOKENIZER", value_parser = TOKENIZER_NAMES.to_vec())]
+    pub tokenizer: Option<String>,
+
+    /// Match words typed without their accents, off by default
+    #[arg(long, env = "SPELL_CHECK_ACCENTS", value_enum)]
+    pub accents: Option<AccentMode>,
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Use this configuration file instead of discovering `.spellcheck.toml` files
+    #[arg(long, value_name = "FILE", conflicts_with = "no_config")]
+    pub config: Option<String>,
+
+    /// Ignore every configuration file
+    #[arg(long)]
+    pub no_config: bool,
+}
+
+// How `check` and `suggest` reach the daemon
+#[derive(Args)]
+pub struct DaemonClientArgs {
+    /// Unix socket of the daemon, used when it runs with the same configuration
+    #[arg(long, env = "SPELL_CHECK_SOCKET", default_value_t = default_socket_path())]
+    pub socket: String,
+
+    /// Check in this process even when a daemon is running
+    #[arg(long)]
+    pub no_daemon: bool,
+}
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Files to check, the format is picked from the extension; directories are
+    /// walked for the files matching the configured `include` globs
+    #[arg(required = true)]
+    pub files: Vec<String>,
+
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Report only the findings missing from this baseline file
+    #[arg(long, value_name = "FILE", conflicts_with = "write_baseline")]
+    pub baseline: Option<String>,
+
+    /// Record the current findings to this baseline file instead of reporting them
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<String>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+
+    #[command(flatten)]
+    pub daemon: DaemonClientArgs,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Unified diff to read; stdin when missing or `-`
+    #[arg(conflicts_with = "staged")]
+    pub diff: Option<String>,
+
+    /// Check the changes staged for commit, for a git pre-commit hook
+    #[arg(long)]
+    pub staged: bool,
+
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Report only the added findings missing from this baseline file
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<String>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+}
+
+#[derive(Args)]
+pub struct FixArgs {
+    /// Files or directories to fix, picked like for `check`
+    #[arg(required = true)]
+    pub files: Vec<String>,
+
+    /// Least confidence, from 0 to 1, of the top suggestion for it to be applied
+    #[arg(long, default_value_t = DEFAULT_CONFIDENCE)]
+    pub threshold: f64,
+
+    /// Rewrite the files instead of printing a unified diff
+    #[arg(short, long)]
+    pub in_place: bool,
+
+    /// Suffix of the copy of each file kept before rewriting it
+    #[arg(long, default_value = ".orig", requires = "in_place")]
+    pub backup_suffix: String,
+
+    /// Rewrite files without keeping a copy
+    #[arg(long, requires = "in_place")]
+    pub no_backup: bool,
+
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+}
+
+#[derive(Args)]
+pub struct InteractiveArgs {
+    /// Files or directories to correct, picked like for `check`
+    #[arg(required = true)]
+    pub files: Vec<String>,
+
+    /// Suffix of the copy of each file kept before rewriting it
+    #[arg(long, default_value = ".orig")]
+    pub backup_suffix: String,
+
+    /// Rewrite files without keeping a copy
+    #[arg(long)]
+    pub no_backup: bool,
+
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+}
+
+// Options of the editor integrations, which check text sent by the editor
+#[derive(Args)]
+pub struct EditorArgs {
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8081")]
+    pub listen: String,
+
+    /// Largest request body accepted, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY_SIZE)]
+    pub max_body_size: usize,
+
+    #[command(flatten)]
+    pub editor: EditorArgs,
+}
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Unix socket to listen on
+    #[arg(long, env = "SPELL_CHECK_SOCKET", default_value_t = default_socket_path())]
+    pub socket: String,
+
+    /// Ask the daemon listening on the socket to stop
+    #[arg(long)]
+    pub stop: bool,
+
+    #[command(flatten)]
+    pub editor: EditorArgs,
+}
+
+#[derive(Args)]
+pub struct SuggestArgs {
+    pub word: String,
+
+    /// [default: levenshtein]
+    #[arg(short, long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+
+    #[command(flatten)]
+    pub daemon: DaemonClientArgs,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Text to check
+    pub dataset: String,
+
+    /// Algorithms to compare; repeat to pick several, all by default
+    #[arg(short, long, value_enum)]
+    pub algorithm: Vec<Algorithm>,
+
+    #[command(flatten)]
+    pub dictionary: DictionaryArgs,
+
+    #[command(flatten)]
+    pub checker: CheckerArgs,
+}
+
+#[derive(Args)]
+pub struct BuildIndexArgs {
+    #[arg(short, long, value_name = "FILE", default_value = DEFAULT_DICTIONARY)]
+    pub dictionary: String,
+
+    #[arg(short, long, value_name = "FILE", default_value = DEFAULT_INDEX)]
+    pub output: String,
+
+    /// Largest edit distance between linked words
+    #[arg(long, default_value_t = DEFAULT_GRAPH_DISTANCE)]
+    pub distance: usize,
+}
+
+#[derive(Args)]
+pub struct AuditArgs {
+    #[arg(default_value = DEFAULT_DICTIONARY)]
+    pub dictionary: String,
+
+    /// "word count" lines; enables near-duplicate detection
+    #[arg(long, value_name = "FILE")]
+    pub frequencies: Option<String>,
+
+    /// How many times more frequent a neighbor must be to flag a near-duplicate
+    #[arg(long, default_value_t = DEFAULT_FREQUENCY_RATIO)]
+    pub ratio: u64,
+}
+
+#[derive(Args)]
+pub struct SimilarArgs {
+    pub word: String,
+
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_INDEX)]
+    pub index: String,
+}
+
+#[derive(Args)]
+pub struct ConfigCommandArgs {
+    /// File or directory whose configuration is printed
+    #[arg(default_value = ".")]
+    pub path: String,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
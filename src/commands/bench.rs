@@ -0,0 +1,252 @@
+use crate::cli::{Algorithm, BenchArgs};
+use crate::config::Config;
+use crate::frontend::fragment::{
+    extractor_for_path, frontend_for_path, is_plain_text, tokenize_fragments, Fragment,
+};
+use crate::spell_check::misspelling::{
+    check_file_streaming, check_tokens, CheckResult, CheckSummary,
+};
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::read_dataset::{read_dataset, DEFAULT_CHUNK_SIZE};
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::{tokenizer_by_name, Token, Tokenizer};
+use crate::{
+    build_checker, load_accent_index, load_dictionaries, with_checker_layers, with_file_path,
+};
+use clap::ValueEnum;
+use cudarc::driver::{CudaDevice, DriverError};
+use cudarc::nvrtc::Ptx;
+use log::{debug, error, info};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::process::ExitCode;
+use std::time::Instant;
+
+// CUDA failing is the only error returned; the others are logged
+pub fn bench(args: &BenchArgs) -> Result<ExitCode, DriverError> {
+    // Initialize the CUDA API with cudarc
+    let dev = CudaDevice::new(0)?;
+
+    dev.load_ptx(
+        Ptx::from_file("src/cuda/suggest_corrections_kernel.ptx"),
+        "cuda",
+        &["suggest_corrections_kernel."],
+    )?;
+
+    let function = dev.get_func("cuda", "suggest_corrections_kernel").unwrap();
+
+    let dataset_file_path = args.dataset.as_str();
+    // Benchmarks are not affected by the project configuration files
+    let mut config = Config::default();
+    config.apply_args(None, &args.dictionary, &args.checker);
+    let config = config.with_defaults();
+
+    // Plain text is streamed chunk by chunk rather than read whole
+    let streaming = is_plain_text(frontend_for_path(dataset_file_path));
+    let (dictionary, dataset) =
+        match load_data(&config.dictionary_files(), dataset_file_path, streaming) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("{}", e);
+                return Ok(ExitCode::from(2));
+            }
+        };
+    let tokenizer = tokenizer_by_name(config.checker.tokenizer()).unwrap();
+    let filter = TokenFilter::new(config.filter.options());
+    let fragments = if streaming {
+        Vec::new()
+    } else {
+        extractor_for_path(dataset_file_path).extract(&dataset)
+    };
+    let (dataset_words, dictionary_words) =
+        tokenize_data(&dataset, &fragments, &dictionary, &*tokenizer, &filter);
+
+    // Every algorithm by default, the neighbor graph only once it has been built
+    let algorithms = if args.algorithm.is_empty() {
+        Algorithm::value_variants()
+            .iter()
+            .copied()
+            .filter(|&algorithm| {
+                algorithm != Algorithm::NeighborGraph
+                    || std::path::Path::new(&args.dictionary.index).exists()
+            })
+            .collect()
+    } else {
+        args.algorithm.clone()
+    };
+    let accent_index = load_accent_index(&config.checker, &dictionary);
+
+    for algorithm in algorithms {
+        let name = algorithm.name();
+        let checker = match build_checker(
+            algorithm,
+            &dictionary,
+            &args.dictionary.index,
+            config.checker.limits(),
+        ) {
+            Ok(checker) => checker,
+            Err(e) => {
+                error!("{}", e);
+                return Ok(ExitCode::from(2));
+            }
+        };
+        let checked: std::io::Result<()> = with_checker_layers(
+            &*checker,
+            &config.checker,
+            accent_index.as_ref(),
+            |checker| {
+                let (summary, duration_look_up) = if streaming {
+                    check_unknown_words_streaming(dataset_file_path, &*tokenizer, &filter, checker)?
+                } else {
+                    check_unknown_words(&dataset_words, checker)
+                };
+                print_unknown_words_info(&summary, &dictionary_words, duration_look_up, name);
+
+                let unknown_words_set = summary.unknown_words.iter().collect::<HashSet<&String>>();
+                let chunk_size = (unknown_words_set.len() / rayon::current_num_threads()).max(1);
+                let (corrections, duration_correction) =
+                    suggest_corrections(&unknown_words_set, checker, chunk_size);
+
+                print_correction_info(&unknown_words_set, duration_correction, &corrections, name);
+                Ok(())
+            },
+        );
+        if let Err(e) = checked {
+            error!("{}: {}", dataset_file_path, e);
+            return Ok(ExitCode::from(2));
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn load_data(
+    dictionary_file_paths: &[String],
+    dataset_file_path: &str,
+    streaming: bool,
+) -> std::io::Result<(HashSet<String>, String)> {
+    let dictionary = load_dictionaries(dictionary_file_paths)?;
+    let dataset = if streaming {
+        String::new()
+    } else {
+        read_dataset(dataset_file_path).map_err(|e| with_file_path(dataset_file_path, e))?
+    };
+    Ok((dictionary, dataset))
+}
+
+fn tokenize_data<'a, 'b>(
+    dataset: &str,
+    fragments: &'a [Fragment],
+    dictionary: &'b HashSet<String>,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+) -> (Vec<Token<'a>>, Vec<&'b String>) {
+    let dataset_words = tokenize_fragments(dataset, fragments, tokenizer, filter);
+    let dictionary_words = dictionary.par_iter().collect::<Vec<&'b String>>();
+    (dataset_words, dictionary_words)
+}
+
+fn check_unknown_words(
+    dataset_words: &[Token],
+    checker: &dyn SpellChecker,
+) -> (CheckSummary, std::time::Duration) {
+    let start = Instant::now();
+    let result = check_tokens(dataset_words, checker);
+    let duration = start.elapsed();
+    let mut summary = CheckSummary::default();
+    debug_misspellings(&result);
+    summary.add(&result);
+    (summary, duration)
+}
+
+// Only the totals are kept, whatever the size of the dataset
+fn check_unknown_words_streaming(
+    dataset_file_path: &str,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+) -> std::io::Result<(CheckSummary, std::time::Duration)> {
+    let start = Instant::now();
+    let mut summary = CheckSummary::default();
+    check_file_streaming(
+        dataset_file_path,
+        DEFAULT_CHUNK_SIZE,
+        tokenizer,
+        filter,
+        checker,
+        |_, result| {
+            debug_misspellings(&result);
+            summary.add(&result);
+            Ok(())
+        },
+    )?;
+    let duration = start.elapsed();
+    Ok((summary, duration))
+}
+
+fn suggest_corrections(
+    unknown_words_set: &HashSet<&String>,
+    checker: &dyn SpellChecker,
+    chunk_size: usize,
+) -> (Vec<Vec<String>>, std::time::Duration) {
+    let start = Instant::now();
+    let unknown_words_vec: Vec<_> = unknown_words_set.clone().into_iter().collect();
+    let corrections: Vec<_> = unknown_words_vec
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            chunk
+                .par_iter()
+                .map(|word| checker.suggest_correction(word.as_str()))
+                .collect::<Vec<_>>()
+        })
+        .flatten()
+        .collect();
+    let duration = start.elapsed();
+    (corrections, duration)
+}
+
+fn debug_misspellings(result: &CheckResult) {
+    for misspelling in &result.misspellings {
+        debug!(
+            "{}:{}: {}",
+            misspelling.line, misspelling.column, misspelling.word
+        );
+    }
+}
+
+fn print_unknown_words_info(
+    summary: &CheckSummary,
+    dictionary_words: &Vec<&String>,
+    duration: std::time::Duration,
+    name: &str,
+) {
+    info!("__________________________________________________________________________");
+    info!("Unknown words {}: {:?}", name, summary.unknown_words.len());
+    info!("Misspelled occurrences {}: {}", name, summary.misspellings);
+    info!("Dictionary words: {}", dictionary_words.len());
+    info!("Dataset words: {}", summary.tokens_checked);
+    info!(
+        "Time elapsed in checking unknown words using {}: {:?}",
+        name, duration
+    );
+    info!("__________________________________________________________________________");
+}
+
+fn print_correction_info(
+    unknown_words_set: &HashSet<&String>,
+    duration: std::time::Duration,
+    corrections: &Vec<Vec<String>>,
+    name: &str,
+) {
+    info!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    info!("set of unknown words: {:?}", unknown_words_set.len());
+    info!(
+        "Time elapsed in checking unknown words using {} correction: {:?}",
+        name, duration
+    );
+
+    let non_empty_corrections: Vec<_> = corrections.iter().filter(|c| !c.is_empty()).collect();
+
+    info!("{} corrections: {:?}", name, non_empty_corrections.len());
+    debug!("{} corrections: {:?}", name, non_empty_corrections);
+    info!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+}
@@ -0,0 +1,466 @@
+use crate::cli::{Algorithm, CheckArgs, CheckerArgs, ConfigArgs, DictionaryArgs};
+use crate::config::{Config, ConfigCache};
+use crate::daemon::{DaemonClient, DaemonCommand, Response};
+use crate::frontend::fragment::{is_plain_text, tokenize_fragments};
+use crate::spell_check::misspelling::{check_file_streaming, check_tokens, CheckResult};
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::baseline::{Baseline, BaselineMode};
+use crate::utils::io::{Finding, OutputFormat, Reporter};
+use crate::utils::read_dataset::{read_dataset, DEFAULT_CHUNK_SIZE};
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use crate::with_configured_checker;
+use log::{debug, error, info};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+pub fn check_files(args: &CheckArgs) -> ExitCode {
+    let (groups, mut failed) = match configured_file_groups(
+        &args.files,
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(groups) => groups,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let Some(mut reporter) = create_reporter(args.format, &args.output) else {
+        return ExitCode::from(2);
+    };
+    let mut baseline = match (&args.baseline, &args.write_baseline) {
+        (Some(file_path), _) => match Baseline::load(file_path) {
+            Ok(baseline) => BaselineMode::Filter(baseline),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                return ExitCode::from(2);
+            }
+        },
+        (None, Some(_)) => BaselineMode::Write(Baseline::new()),
+        (None, None) => BaselineMode::Off,
+    };
+
+    let mut daemon = if args.daemon.no_daemon {
+        None
+    } else {
+        DaemonClient::connect(&args.daemon.socket)
+    };
+    let mut misspelled = false;
+    for (config, file_paths) in &groups {
+        if let Some(client) = &mut daemon {
+            match check_file_group_with_daemon(
+                client,
+                config,
+                &args.dictionary.index,
+                file_paths,
+                &mut reporter,
+                &mut baseline,
+            ) {
+                Ok(Some((group_misspelled, group_failed))) => {
+                    misspelled |= group_misspelled;
+                    failed |= group_failed;
+                    continue;
+                }
+                Ok(None) => debug!("The daemon runs with another configuration"),
+                Err(e) => {
+                    error!("The daemon failed, checking in this process: {}", e);
+                    daemon = None;
+                }
+            }
+        }
+
+        let checked = with_configured_checker(
+            config,
+            &args.dictionary.index,
+            |tokenizer, filter, checker| {
+                check_file_group(
+                    config,
+                    file_paths,
+                    tokenizer,
+                    filter,
+                    checker,
+                    &mut reporter,
+                    &mut baseline,
+                )
+            },
+        );
+        match checked {
+            Ok((group_misspelled, group_failed)) => {
+                misspelled |= group_misspelled;
+                failed |= group_failed;
+            }
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if let Err(e) = reporter.finish() {
+        error!("Writing the report failed: {}", e);
+        failed = true;
+    }
+
+    match (&baseline, &args.write_baseline) {
+        (BaselineMode::Write(baseline), Some(file_path)) => match baseline.save(file_path) {
+            Ok(()) => info!("Recorded {} findings in {}", baseline.count(), file_path),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                failed = true;
+            }
+        },
+        // Entries left over were fixed and can be dropped by writing the baseline again
+        (BaselineMode::Filter(baseline), _) if baseline.count() > 0 => {
+            info!("{} baseline findings no longer occur", baseline.count());
+        }
+        _ => {}
+    }
+
+    if failed {
+        ExitCode::from(2)
+    } else if misspelled {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// Reporter writing to `output`, or to stdout; None after logging why it failed
+pub fn create_reporter(
+    format: OutputFormat,
+    output: &Option<String>,
+) -> Option<Reporter<BufWriter<Box<dyn Write>>>> {
+    let out: Box<dyn Write> = match output {
+        Some(file_path) => match File::create(file_path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                return None;
+            }
+        },
+        None => Box::new(std::io::stdout().lock()),
+    };
+    match Reporter::new(format, BufWriter::new(out)) {
+        Ok(reporter) => Some(reporter),
+        Err(e) => {
+            error!("Writing the report failed: {}", e);
+            None
+        }
+    }
+}
+
+// Returns whether any file has misspellings and whether any could not be checked
+pub fn check_file_group(
+    config: &Config,
+    file_paths: &[String],
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+    reporter: &mut Reporter<impl Write>,
+    baseline: &mut BaselineMode,
+) -> (bool, bool) {
+    let mut misspelled = false;
+    let mut failed = false;
+    for file_path in file_paths {
+        let frontend = config.frontend_for(file_path);
+        // Reported part by part, so that a large file is never held whole
+        let mut report_failed = false;
+        let checked = file_findings(
+            file_path,
+            &frontend,
+            config,
+            tokenizer,
+            filter,
+            checker,
+            |text, first_line, findings| {
+                let findings = baseline.apply_to_text(file_path, text, first_line, findings);
+                misspelled |= !findings.is_empty();
+                reporter.report(file_path, &findings).inspect_err(|_| {
+                    report_failed = true;
+                })
+            },
+        );
+        match checked {
+            Err(e) if report_failed => error!("Writing the report failed: {}", e),
+            Err(e) => error!("{}: {}", file_path, e),
+            Ok(()) => continue,
+        }
+        failed = true;
+    }
+    (misspelled, failed)
+}
+
+// The daemon's version of `check_file_group`; None when the daemon runs with another
+// configuration. Nothing is reported until every file is checked, so that a daemon
+// failing halfway leaves the group to be checked in-process.
+fn check_file_group_with_daemon(
+    daemon: &mut DaemonClient,
+    config: &Config,
+    index_file_path: &str,
+    file_paths: &[String],
+    reporter: &mut Reporter<impl Write>,
+    baseline: &mut BaselineMode,
+) -> std::io::Result<Option<(bool, bool)>> {
+    let fingerprint = config.fingerprint(index_file_path);
+    let mut results = Vec::with_capacity(file_paths.len());
+    for file_path in file_paths {
+        // The daemon resolves relative paths against its own directory
+        let absolute_path = match fs::canonicalize(file_path) {
+            Ok(absolute_path) => absolute_path,
+            Err(e) => {
+                results.push(Err(e.to_string()));
+                continue;
+            }
+        };
+        let command = DaemonCommand::Check {
+            file: absolute_path.to_string_lossy().into_owned(),
+            frontend: config.frontend_for(file_path),
+        };
+        match daemon.request(&fingerprint, command)? {
+            Response::Findings { mut findings } => {
+                for finding in &mut findings {
+                    finding.file = file_path.clone();
+                }
+                results.push(Ok(findings));
+            }
+            Response::Error { message } => results.push(Err(message)),
+            Response::Mismatch => return Ok(None),
+            response => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("unexpected answer from the daemon: {:?}", response),
+                ))
+            }
+        }
+    }
+
+    let mut misspelled = false;
+    let mut failed = false;
+    for (file_path, result) in file_paths.iter().zip(results) {
+        let result = result.and_then(|findings| {
+            baseline
+                .apply(file_path, findings)
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(findings) => {
+                if let Err(e) = reporter.report(file_path, &findings) {
+                    error!("Writing the report failed: {}", e);
+                    failed = true;
+                }
+                misspelled |= !findings.is_empty();
+            }
+            Err(message) => {
+                error!("{}: {}", file_path, message);
+                failed = true;
+            }
+        }
+    }
+    Ok(Some((misspelled, failed)))
+}
+
+// Misspellings of one file with their suggestions, emitted like by `check_file_chunks`
+pub fn file_findings(
+    file_path: &str,
+    frontend: &str,
+    config: &Config,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+    mut emit: impl FnMut(&str, usize, Vec<Finding>) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let checker_name = config.checker.algorithm().name();
+    let (mut misspellings, mut tokens_checked) = (0, 0);
+    check_file_chunks(
+        file_path,
+        frontend,
+        config,
+        tokenizer,
+        filter,
+        checker,
+        |text, first_line, result| {
+            misspellings += result.misspellings.len();
+            tokens_checked += result.tokens_checked;
+            let findings = result_findings(file_path, &result, checker_name, checker);
+            emit(text, first_line, findings)
+        },
+    )?;
+    info!(
+        "{}: {} misspellings in {} words",
+        file_path, misspellings, tokens_checked
+    );
+    Ok(())
+}
+
+// Files sharing one effective configuration, and so one checker
+pub type FileGroup = (Config, Vec<String>);
+
+// Also returns whether any path could not be read
+pub fn configured_file_groups(
+    paths: &[String],
+    config_args: &ConfigArgs,
+    algorithm: Option<Algorithm>,
+    dictionary: &DictionaryArgs,
+    checker: &CheckerArgs,
+) -> std::io::Result<(Vec<FileGroup>, bool)> {
+    let mut configs = ConfigCache::new(config_args)?;
+
+    let mut failed = false;
+    let mut file_paths = Vec::new();
+    for path in paths {
+        let result = if Path::new(path).is_dir() {
+            walk_dir(Path::new(path), &mut configs, &mut file_paths)
+        } else {
+            file_paths.push(path.clone());
+            Ok(())
+        };
+        if let Err(e) = result {
+            error!("{}: {}", path, e);
+            failed = true;
+        }
+    }
+
+    let mut groups: Vec<FileGroup> = Vec::new();
+    for file_path in file_paths {
+        let mut config = match configs.config_for(Path::new(&file_path)) {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                failed = true;
+                continue;
+            }
+        };
+        config.apply_args(algorithm, dictionary, checker);
+        let config = config.with_defaults();
+        match groups.iter_mut().find(|(other, _)| *other == config) {
+            Some((_, file_paths)) => file_paths.push(file_path),
+            None => groups.push((config, vec![file_path])),
+        }
+    }
+    Ok((groups, failed))
+}
+
+// Collects the files under `dir` included by their configuration, skipping hidden directories
+fn walk_dir(
+    dir: &Path,
+    configs: &mut ConfigCache,
+    file_paths: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if !hidden && !configs.config_for(&path)?.excludes_dir(&path) {
+                walk_dir(&path, configs, file_paths)?;
+            }
+        } else if configs.config_for(&path)?.includes(&path) {
+            file_paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+// Findings of the misspellings of `file_path` in `result`, with their suggestions
+pub fn result_findings(
+    file_path: &str,
+    result: &CheckResult,
+    checker_name: &str,
+    checker: &dyn SpellChecker,
+) -> Vec<Finding> {
+    let suggestions = suggest_unknown_words(result, checker);
+    result
+        .misspellings
+        .iter()
+        .map(|misspelling| Finding {
+            file: file_path.to_string(),
+            line: misspelling.line,
+            column: misspelling.column,
+            word: misspelling.word.clone(),
+            suggestions: suggestions[&nfc(&misspelling.word).to_lowercase()].clone(),
+            checker: checker_name.to_string(),
+        })
+        .collect()
+}
+
+// Suggestions for each distinct unknown word, keyed the way `CheckResult::unknown_words` is
+pub fn suggest_unknown_words(
+    result: &CheckResult,
+    checker: &dyn SpellChecker,
+) -> HashMap<String, Vec<String>> {
+    result
+        .unknown_words()
+        .into_par_iter()
+        .map(|word| {
+            let suggestions = checker.suggest_correction(&word);
+            (word, suggestions)
+        })
+        .collect()
+}
+
+// Misspellings of a whole file, for commands that rewrite it
+pub fn check_file(
+    file_path: &str,
+    frontend: &str,
+    config: &Config,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+) -> std::io::Result<CheckResult> {
+    let mut result = CheckResult::default();
+    check_file_chunks(
+        file_path,
+        frontend,
+        config,
+        tokenizer,
+        filter,
+        checker,
+        |_, _, chunk_result| {
+            result.misspellings.extend(chunk_result.misspellings);
+            result.tokens_checked += chunk_result.tokens_checked;
+            Ok(())
+        },
+    )?;
+    Ok(result)
+}
+
+// Plain text is streamed chunk by chunk, other formats are read whole and extracted.
+// `emit` gets each checked text in file order, with the number of its first line
+pub fn check_file_chunks(
+    file_path: &str,
+    frontend: &str,
+    config: &Config,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+    mut emit: impl FnMut(&str, usize, CheckResult) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    if is_plain_text(frontend) {
+        return check_file_streaming(
+            file_path,
+            DEFAULT_CHUNK_SIZE,
+            tokenizer,
+            filter,
+            checker,
+            |chunk, result| emit(&chunk.text, chunk.line, result),
+        );
+    }
+
+    let source = read_dataset(file_path)?;
+    let fragments = config.extractor(frontend).unwrap().extract(&source);
+    let tokens = tokenize_fragments(&source, &fragments, tokenizer, filter);
+    emit(&source, 1, check_tokens(&tokens, checker))
+}
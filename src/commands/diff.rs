@@ -0,0 +1,159 @@
+use crate::cli::DiffArgs;
+use crate::commands::check::{configured_file_groups, create_reporter, result_findings};
+use crate::frontend::fragment::tokenize_fragments;
+use crate::spell_check::misspelling::check_tokens;
+use crate::utils::baseline::{Baseline, BaselineMode};
+use crate::utils::diff::parse_unified_diff;
+use crate::with_configured_checker;
+use log::{debug, error};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+pub fn check_diff(args: &DiffArgs) -> ExitCode {
+    let diff = if args.staged {
+        git_output(&[
+            "diff",
+            "--cached",
+            "--relative",
+            "--no-color",
+            "--no-ext-diff",
+            "--unified=0",
+        ])
+    } else {
+        match args.diff.as_deref() {
+            Some(file_path) if file_path != "-" => fs::read_to_string(file_path),
+            _ => std::io::read_to_string(std::io::stdin()),
+        }
+    };
+    let diff = match diff {
+        Ok(diff) => diff,
+        Err(e) => {
+            error!("Reading the diff failed: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let mut changes = parse_unified_diff(&diff)
+        .into_iter()
+        .map(|file| (file.path.clone(), file))
+        .collect::<HashMap<_, _>>();
+
+    let paths = changes.keys().cloned().collect::<Vec<_>>();
+    let (groups, mut failed) = match configured_file_groups(
+        &paths,
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(groups) => groups,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let Some(mut reporter) = create_reporter(args.format, &args.output) else {
+        return ExitCode::from(2);
+    };
+    let mut baseline = match &args.baseline {
+        Some(file_path) => match Baseline::load(file_path) {
+            Ok(baseline) => BaselineMode::Filter(baseline),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                return ExitCode::from(2);
+            }
+        },
+        None => BaselineMode::Off,
+    };
+
+    let mut misspelled = false;
+    for (config, file_paths) in &groups {
+        let checker_name = config.checker.algorithm().name();
+        // Like when walking a directory, so lock files and binaries are left out
+        let mut file_paths = file_paths
+            .iter()
+            .filter(|file_path| config.includes(Path::new(file_path)))
+            .collect::<Vec<_>>();
+        file_paths.sort();
+        if file_paths.is_empty() {
+            continue;
+        }
+
+        let checked = with_configured_checker(
+            config,
+            &args.dictionary.index,
+            |tokenizer, filter, checker| {
+                let mut failed = false;
+                for file_path in file_paths {
+                    // A submodule or other directory shows in the diff but is no file
+                    let Some(file_changes) = changes.remove(file_path.as_str()) else {
+                        continue;
+                    };
+                    let source = if args.staged {
+                        git_output(&["show", &format!(":./{}", file_path)])
+                    } else {
+                        fs::read_to_string(file_path)
+                    };
+                    // Without the new file, the added lines are checked on their own
+                    let source = match source {
+                        Ok(source) if file_changes.matches(&source) => source,
+                        _ => {
+                            debug!("{}: checking the added lines alone", file_path);
+                            file_changes.added_text()
+                        }
+                    };
+
+                    let frontend = config.frontend_for(file_path);
+                    let fragments = config.extractor(&frontend).unwrap().extract(&source);
+                    let tokens = tokenize_fragments(&source, &fragments, tokenizer, filter);
+                    let mut result = check_tokens(&tokens, checker);
+                    result.misspellings.retain(|misspelling| {
+                        file_changes.added_lines.contains_key(&misspelling.line)
+                    });
+
+                    let findings = result_findings(file_path, &result, checker_name, checker);
+                    // Fingerprinted from the checked text, which is the staged one with `--staged`
+                    let findings = baseline.apply_to_text(file_path, &source, 1, findings);
+                    if let Err(e) = reporter.report(file_path, &findings) {
+                        error!("Writing the report failed: {}", e);
+                        failed = true;
+                    }
+                    misspelled |= !findings.is_empty();
+                }
+                failed
+            },
+        );
+        match checked {
+            Ok(group_failed) => failed |= group_failed,
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if let Err(e) = reporter.finish() {
+        error!("Writing the report failed: {}", e);
+        failed = true;
+    }
+
+    if failed {
+        ExitCode::from(2)
+    } else if misspelled {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// Standard output of a git command run in the current directory
+fn git_output(args: &[&str]) -> std::io::Result<String> {
+    let output = std::process::Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
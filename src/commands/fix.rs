@@ -0,0 +1,236 @@
+use crate::cli::{FixArgs, InteractiveArgs};
+use crate::commands::check::{check_file, configured_file_groups, suggest_unknown_words};
+use crate::config::Config;
+use crate::interactive::{Review, Session};
+use crate::spell_check::autofix;
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::load_dictionary::append_words;
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use crate::with_configured_checker;
+use log::{debug, error, info};
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
+
+pub fn fix_files(args: &FixArgs) -> ExitCode {
+    let (groups, mut failed) = match configured_file_groups(
+        &args.files,
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(groups) => groups,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    for (config, file_paths) in &groups {
+        // Every suggestion within the distance is weighed, so that a close runner-up
+        // still lowers the confidence: checkers break ties between equally distant
+        // words arbitrarily, and a top-k cut could drop the intended word
+        let mut config = config.clone();
+        config.checker.top_k = Some(usize::MAX);
+        let config = &config;
+        let fixed = with_configured_checker(
+            config,
+            &args.dictionary.index,
+            |tokenizer, filter, checker| {
+                let mut failed = false;
+                for file_path in file_paths {
+                    if let Err(e) = fix_file(file_path, config, args, tokenizer, filter, checker) {
+                        error!("{}: {}", file_path, e);
+                        failed = true;
+                    }
+                }
+                failed
+            },
+        );
+        match fixed {
+            Ok(group_failed) => failed |= group_failed,
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::from(2)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn fix_file(
+    file_path: &str,
+    config: &Config,
+    args: &FixArgs,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+) -> std::io::Result<()> {
+    let source = fs::read_to_string(file_path)?;
+    let frontend = config.frontend_for(file_path);
+    let result = check_file(file_path, &frontend, config, tokenizer, filter, checker)?;
+    let suggestions = suggest_unknown_words(&result, checker);
+
+    let mut corrections = Vec::new();
+    for misspelling in &result.misspellings {
+        let word = nfc(&misspelling.word).to_lowercase();
+        if !autofix::is_replaceable(&source, &misspelling.range, &misspelling.word) {
+            info!(
+                "{}:{}:{}: {} left as is, it spans markup",
+                file_path, misspelling.line, misspelling.column, misspelling.word
+            );
+            continue;
+        }
+        match autofix::correction(misspelling, &suggestions[&word]) {
+            Some(correction) if correction.confidence >= args.threshold => {
+                debug!(
+                    "{}:{}:{}: {} -> {} ({:.2})",
+                    file_path,
+                    misspelling.line,
+                    misspelling.column,
+                    correction.word,
+                    correction.replacement,
+                    correction.confidence
+                );
+                corrections.push(correction);
+            }
+            _ => info!(
+                "{}:{}:{}: {} left as is, no confident suggestion",
+                file_path, misspelling.line, misspelling.column, misspelling.word
+            ),
+        }
+    }
+    info!(
+        "{}: {} of {} misspellings fixed",
+        file_path,
+        corrections.len(),
+        result.misspellings.len()
+    );
+    if corrections.is_empty() {
+        return Ok(());
+    }
+
+    let fixed = autofix::apply_corrections(&source, &corrections);
+    if !args.in_place {
+        print!("{}", autofix::unified_diff(file_path, &source, &fixed));
+        return Ok(());
+    }
+    if !args.no_backup {
+        fs::copy(file_path, format!("{}{}", file_path, args.backup_suffix))?;
+    }
+    fs::write(file_path, fixed)
+}
+
+pub fn correct_interactively(args: &InteractiveArgs) -> ExitCode {
+    let (groups, mut failed) = match configured_file_groups(
+        &args.files,
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(groups) => groups,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let color = std::io::stdout().is_terminal();
+    let mut session = Session::new(std::io::stdin().lock(), std::io::stdout(), color);
+    for (config, file_paths) in &groups {
+        let personal = config.personal_dictionary.clone().unwrap();
+        let reviewed = with_configured_checker(
+            config,
+            &args.dictionary.index,
+            |tokenizer, filter, checker| {
+                let mut failed = false;
+                for file_path in file_paths {
+                    let result = correct_file(
+                        file_path,
+                        config,
+                        args,
+                        &mut session,
+                        tokenizer,
+                        filter,
+                        checker,
+                    )
+                    .and_then(|()| append_words(&personal, &session.take_added_words()));
+                    if let Err(e) = result {
+                        error!("{}: {}", file_path, e);
+                        failed = true;
+                    }
+                    if session.exited() {
+                        break;
+                    }
+                }
+                failed
+            },
+        );
+        match reviewed {
+            Ok(group_failed) => failed |= group_failed,
+            Err(e) => {
+                error!("{}", e);
+                failed = true;
+            }
+        }
+        if session.exited() {
+            break;
+        }
+    }
+
+    if failed {
+        ExitCode::from(2)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn correct_file(
+    file_path: &str,
+    config: &Config,
+    args: &InteractiveArgs,
+    session: &mut Session<impl std::io::BufRead, impl Write>,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+) -> std::io::Result<()> {
+    let source = fs::read_to_string(file_path)?;
+    let frontend = config.frontend_for(file_path);
+    let mut result = check_file(file_path, &frontend, config, tokenizer, filter, checker)?;
+    result.misspellings.retain(|misspelling| {
+        let replaceable = autofix::is_replaceable(&source, &misspelling.range, &misspelling.word);
+        if !replaceable {
+            info!(
+                "{}:{}:{}: {} skipped, it spans markup",
+                file_path, misspelling.line, misspelling.column, misspelling.word
+            );
+        }
+        replaceable
+    });
+
+    let corrections = match session.review(file_path, &source, &result.misspellings, checker)? {
+        Review::Corrections(corrections) => corrections,
+        Review::Quit => {
+            info!("{}: left unchanged", file_path);
+            return Ok(());
+        }
+    };
+    info!("{}: {} words replaced", file_path, corrections.len());
+    if corrections.is_empty() {
+        return Ok(());
+    }
+
+    if !args.no_backup {
+        fs::copy(file_path, format!("{}{}", file_path, args.backup_suffix))?;
+    }
+    fs::write(file_path, autofix::apply_corrections(&source, &corrections))
+}
@@ -0,0 +1,746 @@
+use crate::cli::{AccentMode, Algorithm, CheckerArgs, ConfigArgs, DictionaryArgs};
+use crate::frontend::fragment::{extractor_by_name, frontend_for_path, Extractor, FRONTEND_NAMES};
+use crate::frontend::html::{HtmlExtractor, DEFAULT_CHECKED_ATTRIBUTES, DEFAULT_SKIPPED_ELEMENTS};
+use crate::spell_check::spell_checker::SuggestionLimits;
+use crate::utils::token_filter::TokenFilterOptions;
+use crate::utils::tokenizer::TOKENIZER_NAMES;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = ".spellcheck.toml";
+pub const DEFAULT_DICTIONARY: &str = "data/dictionary/dict.txt";
+/// Words added from the interactive session, loaded along with the dictionaries.
+pub const PERSONAL_DICTIONARY: &str = ".spellcheck-words.txt";
+
+// Extensions checked when walking a directory whose configs give no `include` globs
+const DEFAULT_TEXT_EXTENSIONS: &[&str] = &["txt", "text", "rst", "adoc"];
+
+/// Project settings read from `.spellcheck.toml` files.
+///
+/// Configs are discovered from the checked file's directory upwards, stopping at one
+/// with `root = true`. Deeper configs extend the word lists and patterns of the ones
+/// above them and override their scalar settings. Relative paths and globs are
+/// resolved against the directory of the config that declares them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Stops discovery here, for the top-level config of a project
+    pub root: bool,
+    pub dictionaries: Vec<String>,
+    /// Word list that the interactive session adds to, loaded when it exists
+    pub personal_dictionary: Option<String>,
+    /// Words never reported as misspelled
+    pub ignore_words: Vec<String>,
+    /// Files of words never reported, one per line
+    pub ignore_word_lists: Vec<String>,
+    /// Regexes matched against the text; words overlapping a match are skipped
+    pub ignore_patterns: Vec<String>,
+    /// Globs of the files checked when walking a directory
+    pub include: Vec<String>,
+    /// Globs of files and directories never checked when walking a directory
+    pub exclude: Vec<String>,
+    /// Front-end by file extension, overriding the built-in choice
+    pub frontends: BTreeMap<String, String>,
+    pub checker: CheckerConfig,
+    pub filter: FilterConfig,
+    pub html: HtmlConfig,
+    /// `include` and `exclude` compiled once, for the directory walk
+    #[serde(skip)]
+    globs: Globs,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CheckerConfig {
+    pub algorithm: Option<Algorithm>,
+    pub distance: Option<usize>,
+    pub top_k: Option<usize>,
+    pub tokenizer: Option<String>,
+    pub accents: Option<AccentMode>,
+}
+
+/// Which kinds of non-word text are skipped, see `TokenFilterOptions`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FilterConfig {
+    pub urls: Option<bool>,
+    pub emails: Option<bool>,
+    pub paths: Option<bool>,
+    pub hashes: Option<bool>,
+    pub versions: Option<bool>,
+    pub numbers: Option<bool>,
+    pub acronyms: Option<bool>,
+}
+
+/// What the HTML front-end checks, replacing its built-in lists when set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HtmlConfig {
+    /// Elements whose content is never checked
+    pub skip_elements: Option<Vec<String>>,
+    /// Attributes whose values are checked
+    pub check_attributes: Option<Vec<String>>,
+}
+
+/// Regexes of the `include` and `exclude` globs, see `glob_regex`.
+#[derive(Debug, Clone, Default)]
+struct Globs {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+// Compiled from the same globs, so comparing these compares the globs
+impl PartialEq for Globs {
+    fn eq(&self, other: &Self) -> bool {
+        fn patterns(regexes: &[Regex]) -> impl Iterator<Item = &str> {
+            regexes.iter().map(Regex::as_str)
+        }
+        patterns(&self.include).eq(patterns(&other.include))
+            && patterns(&self.exclude).eq(patterns(&other.exclude))
+    }
+}
+
+impl Config {
+    /// Reads one config file, resolving its paths and globs against its directory.
+    pub fn load(file_path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(file_path)?;
+        let mut config: Config = toml::from_str(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", file_path.display(), e),
+            )
+        })?;
+        config.validate().map_err(|message| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", file_path.display(), message),
+            )
+        })?;
+
+        let dir = fs::canonicalize(file_path)?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let resolve = |path: &String| dir.join(path).to_string_lossy().into_owned();
+        config.dictionaries = config.dictionaries.iter().map(resolve).collect();
+        config.personal_dictionary = config.personal_dictionary.as_ref().map(resolve);
+        config.ignore_word_lists = config.ignore_word_lists.iter().map(resolve).collect();
+        config.include = config
+            .include
+            .iter()
+            .map(|glob| anchor_glob(&dir, glob))
+            .collect();
+        config.exclude = config
+            .exclude
+            .iter()
+            .map(|glob| anchor_glob(&dir, glob))
+            .collect();
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| glob_regex(glob))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|message| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {}", file_path.display(), message),
+                    )
+                })
+        };
+        config.globs = Globs {
+            include: compile(&config.include)?,
+            exclude: compile(&config.exclude)?,
+        };
+        Ok(config)
+    }
+
+    /// The merged configs applying to `path`, from the outermost directory inwards.
+    pub fn discover(path: &Path) -> io::Result<Self> {
+        let path = fs::canonicalize(path)?;
+        let mut dir = if path.is_dir() {
+            Some(path.as_path())
+        } else {
+            path.parent()
+        };
+
+        let mut layers = Vec::new();
+        while let Some(current) = dir {
+            let file_path = current.join(CONFIG_FILE_NAME);
+            if file_path.is_file() {
+                let config = Config::load(&file_path)?;
+                let root = config.root;
+                layers.push(config);
+                if root {
+                    break;
+                }
+            }
+            dir = current.parent();
+        }
+
+        let mut config = Config::default();
+        for layer in layers.into_iter().rev() {
+            config.merge(layer);
+        }
+        Ok(config)
+    }
+
+    /// Applies a deeper config on top of this one.
+    pub fn merge(&mut self, inner: Config) {
+        self.root |= inner.root;
+        self.dictionaries.extend(inner.dictionaries);
+        self.personal_dictionary = inner
+            .personal_dictionary
+            .or(self.personal_dictionary.take());
+        self.ignore_words.extend(inner.ignore_words);
+        self.ignore_word_lists.extend(inner.ignore_word_lists);
+        self.ignore_patterns.extend(inner.ignore_patterns);
+        if !inner.include.is_empty() {
+            self.include = inner.include;
+            self.globs.include = inner.globs.include;
+        }
+        self.exclude.extend(inner.exclude);
+        self.globs.exclude.extend(inner.globs.exclude);
+        self.frontends.extend(inner.frontends);
+        self.checker.merge(inner.checker);
+        self.filter.merge(inner.filter);
+        self.html.merge(inner.html);
+    }
+
+    /// Applies the command-line options, whose dictionaries replace the configured ones.
+    pub fn apply_args(
+        &mut self,
+        algorithm: Option<Algorithm>,
+        dictionary: &DictionaryArgs,
+        checker: &CheckerArgs,
+    ) {
+        if !dictionary.dictionaries.is_empty() {
+            self.dictionaries = dictionary.dictionaries.clone();
+        }
+        if dictionary.personal.is_some() {
+            self.personal_dictionary = dictionary.personal.clone();
+        }
+        self.checker.merge(CheckerConfig {
+            algorithm,
+            distance: checker.distance,
+            top_k: checker.top_k,
+            tokenizer: checker.tokenizer.clone(),
+            accents: checker.accents,
+        });
+    }
+
+    /// Fills every unset setting with its built-in default.
+    pub fn with_defaults(mut self) -> Self {
+        if self.dictionaries.is_empty() {
+            self.dictionaries.push(DEFAULT_DICTIONARY.to_string());
+        }
+        self.personal_dictionary
+            .get_or_insert_with(|| PERSONAL_DICTIONARY.to_string());
+        let checker = &mut self.checker;
+        let limits = SuggestionLimits::default();
+        checker.algorithm.get_or_insert(Algorithm::Levenshtein);
+        checker.distance.get_or_insert(limits.max_distance);
+        checker.top_k.get_or_insert(limits.top_k);
+        checker.tokenizer.get_or_insert_with(|| "regex".to_string());
+
+        let options = TokenFilterOptions::default();
+        let filter = &mut self.filter;
+        filter.urls.get_or_insert(options.urls);
+        filter.emails.get_or_insert(options.emails);
+        filter.paths.get_or_insert(options.paths);
+        filter.hashes.get_or_insert(options.hashes);
+        filter.versions.get_or_insert(options.versions);
+        filter.numbers.get_or_insert(options.numbers);
+        filter.acronyms.get_or_insert(options.acronyms);
+
+        let html = &mut self.html;
+        html.skip_elements
+            .get_or_insert_with(|| to_strings(DEFAULT_SKIPPED_ELEMENTS));
+        html.check_attributes
+            .get_or_insert_with(|| to_strings(DEFAULT_CHECKED_ATTRIBUTES));
+        self
+    }
+
+    /// The dictionaries, followed by the personal dictionary once words were added to it.
+    pub fn dictionary_files(&self) -> Vec<String> {
+        let mut file_paths = self.dictionaries.clone();
+        if let Some(personal) = &self.personal_dictionary {
+            if Path::new(personal).is_file() {
+                file_paths.push(personal.clone());
+            }
+        }
+        file_paths
+    }
+
+    /// Identifies what a checker built from this config accepts and suggests, so that
+    /// the daemon's checker is only used when it would answer the same. Edited word
+    /// lists change it.
+    pub fn fingerprint(&self, index_file_path: &str) -> String {
+        let mut file_paths = self.dictionary_files();
+        file_paths.extend(self.ignore_word_lists.iter().cloned());
+        if self.checker.algorithm() == Algorithm::NeighborGraph {
+            file_paths.push(index_file_path.to_string());
+        }
+        let files = file_paths
+            .iter()
+            .map(|file_path| {
+                let path = fs::canonicalize(file_path).unwrap_or_else(|_| PathBuf::from(file_path));
+                let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+                (path, modified.ok())
+            })
+            .collect::<Vec<_>>();
+
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        files.hash(&mut hasher);
+        self.ignore_words.hash(&mut hasher);
+        self.ignore_patterns.hash(&mut hasher);
+        serde_json::to_string(&(&self.checker, &self.filter, &self.html))
+            .unwrap()
+            .hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// The front-end named `frontend`, with the `[html]` settings applied.
+    pub fn extractor(&self, frontend: &str) -> Option<Box<dyn Extractor>> {
+        if frontend != "html" {
+            return extractor_by_name(frontend);
+        }
+        let mut extractor = HtmlExtractor::new();
+        if let Some(elements) = &self.html.skip_elements {
+            extractor = extractor.with_skipped_elements(elements);
+        }
+        if let Some(attributes) = &self.html.check_attributes {
+            extractor = extractor.with_checked_attributes(attributes);
+        }
+        Some(Box::new(extractor))
+    }
+
+    /// Name of the front-end for `path`, taking the `frontends` overrides into account.
+    pub fn frontend_for(&self, path: &str) -> String {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match self.frontends.get(&extension) {
+            Some(frontend) => frontend.clone(),
+            None => frontend_for_path(path).to_string(),
+        }
+    }
+
+    /// Whether a file found while walking a directory is checked.
+    pub fn includes(&self, path: &Path) -> bool {
+        let path = absolute_path(path);
+        if self.globs.exclude.iter().any(|regex| regex.is_match(&path)) {
+            return false;
+        }
+        if self.include.is_empty() {
+            let frontend = self.frontend_for(&path);
+            let extension = Path::new(&path)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or("");
+            return frontend != "plain" || DEFAULT_TEXT_EXTENSIONS.contains(&extension);
+        }
+        self.globs.include.iter().any(|regex| regex.is_match(&path))
+    }
+
+    /// Whether a directory is walked at all.
+    pub fn excludes_dir(&self, path: &Path) -> bool {
+        let path = absolute_path(path);
+        self.globs.exclude.iter().any(|regex| regex.is_match(&path))
+    }
+
+    /// `ignore_words` together with the words of every `ignore_word_lists` file.
+    pub fn load_ignored_words(&self) -> io::Result<Vec<String>> {
+        let mut words = self.ignore_words.clone();
+        for file_path in &self.ignore_word_lists {
+            let contents = fs::read_to_string(file_path)?;
+            words.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty() && !word.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        Ok(words)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(tokenizer) = &self.checker.tokenizer {
+            if !TOKENIZER_NAMES.contains(&tokenizer.as_str()) {
+                return Err(format!(
+                    "unknown tokenizer {:?}, expected one of {:?}",
+                    tokenizer, TOKENIZER_NAMES
+                ));
+            }
+        }
+        for frontend in self.frontends.values() {
+            if !FRONTEND_NAMES.contains(&frontend.as_str()) {
+                return Err(format!(
+                    "unknown front-end {:?}, expected one of {:?}",
+                    frontend, FRONTEND_NAMES
+                ));
+            }
+        }
+        for pattern in &self.ignore_patterns {
+            Regex::new(pattern).map_err(|e| e.to_string())?;
+        }
+        for glob in self.include.iter().chain(&self.exclude) {
+            glob_pattern(glob)
+                .map_err(|message| format!("invalid glob {:?}: {}", glob, message))?;
+        }
+        Ok(())
+    }
+}
+
+impl CheckerConfig {
+    pub fn merge(&mut self, inner: CheckerConfig) {
+        self.algorithm = inner.algorithm.or(self.algorithm);
+        self.distance = inner.distance.or(self.distance);
+        self.top_k = inner.top_k.or(self.top_k);
+        self.tokenizer = inner.tokenizer.or(self.tokenizer.take());
+        self.accents = inner.accents.or(self.accents);
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm.unwrap_or(Algorithm::Levenshtein)
+    }
+
+    pub fn limits(&self) -> SuggestionLimits {
+        let defaults = SuggestionLimits::default();
+        SuggestionLimits {
+            max_distance: self.distance.unwrap_or(defaults.max_distance),
+            top_k: self.top_k.unwrap_or(defaults.top_k),
+        }
+    }
+
+    pub fn tokenizer(&self) -> &str {
+        self.tokenizer.as_deref().unwrap_or("regex")
+    }
+}
+
+impl FilterConfig {
+    pub fn merge(&mut self, inner: FilterConfig) {
+        self.urls = inner.urls.or(self.urls);
+        self.emails = inner.emails.or(self.emails);
+        self.paths = inner.paths.or(self.paths);
+        self.hashes = inner.hashes.or(self.hashes);
+        self.versions = inner.versions.or(self.versions);
+        self.numbers = inner.numbers.or(self.numbers);
+        self.acronyms = inner.acronyms.or(self.acronyms);
+    }
+
+    pub fn options(&self) -> TokenFilterOptions {
+        let defaults = TokenFilterOptions::default();
+        TokenFilterOptions {
+            urls: self.urls.unwrap_or(defaults.urls),
+            emails: self.emails.unwrap_or(defaults.emails),
+            paths: self.paths.unwrap_or(defaults.paths),
+            hashes: self.hashes.unwrap_or(defaults.hashes),
+            versions: self.versions.unwrap_or(defaults.versions),
+            numbers: self.numbers.unwrap_or(defaults.numbers),
+            acronyms: self.acronyms.unwrap_or(defaults.acronyms),
+        }
+    }
+}
+
+impl HtmlConfig {
+    pub fn merge(&mut self, inner: HtmlConfig) {
+        self.skip_elements = inner.skip_elements.or(self.skip_elements.take());
+        self.check_attributes = inner.check_attributes.or(self.check_attributes.take());
+    }
+}
+
+fn to_strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+/// Finds the config of each file, discovering configs once per directory.
+pub struct ConfigCache {
+    // Config given with `--config`, or the empty one with `--no-config`, used for every file
+    fixed: Option<Config>,
+    configs: HashMap<PathBuf, Config>,
+}
+
+impl ConfigCache {
+    pub fn new(args: &ConfigArgs) -> io::Result<Self> {
+        let fixed = if args.no_config {
+            Some(Config::default())
+        } else {
+            args.config
+                .as_ref()
+                .map(|file_path| Config::load(Path::new(file_path)))
+                .transpose()?
+        };
+        Ok(ConfigCache {
+            fixed,
+            configs: HashMap::new(),
+        })
+    }
+
+    pub fn config_for(&mut self, path: &Path) -> io::Result<&Config> {
+        if let Some(config) = &self.fixed {
+            return Ok(config);
+        }
+
+        // A missing file, like one a diff adds, takes the config of its closest directory
+        let path = path
+            .ancestors()
+            .map(|path| {
+                if path.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    path
+                }
+            })
+            .find_map(|path| fs::canonicalize(path).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such directory"))?;
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().map(Path::to_path_buf).unwrap_or_default()
+        };
+        if !self.configs.contains_key(&dir) {
+            let config = Config::discover(&dir)?;
+            self.configs.insert(dir.clone(), config);
+        }
+        Ok(&self.configs[&dir])
+    }
+}
+
+// Globs are anchored at the canonical directory of their config, so paths are matched
+// in the same form
+fn absolute_path(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Globs without a `/` match at any depth below `dir`, like in `.gitignore`
+fn anchor_glob(dir: &Path, glob: &str) -> String {
+    let dir = dir.to_string_lossy();
+    let dir = dir.trim_end_matches('/');
+    match glob.strip_prefix('/') {
+        Some(anchored) => format!("{}/{}", dir, anchored),
+        None if glob.contains('/') => format!("{}/{}", dir, glob),
+        None => format!("{}/**/{}", dir, glob),
+    }
+}
+
+fn glob_regex(glob: &str) -> Result<Regex, String> {
+    let pattern =
+        glob_pattern(glob).map_err(|message| format!("invalid glob {:?}: {}", glob, message))?;
+    Regex::new(&pattern).map_err(|e| e.to_string())
+}
+
+// `*` and `?` stay within a path component, `**` crosses them, `{a,b}` is either; a
+// glob matching a directory also matches everything inside it
+fn glob_pattern(glob: &str) -> Result<String, &'static str> {
+    let mut pattern = String::from("^");
+    // Number of `{` left open
+    let mut depth = 0;
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '{' => {
+                depth += 1;
+                pattern.push_str("(?:");
+            }
+            '}' if depth == 0 => return Err("unmatched `}`"),
+            '}' => {
+                depth -= 1;
+                pattern.push(')');
+            }
+            ',' if depth > 0 => pattern.push('|'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if depth > 0 {
+        return Err("unclosed `{`");
+    }
+    pattern.push_str("(?:/.*)?$");
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A directory tree of its own, removed when dropped
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("config-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempTree(fs::canonicalize(dir).unwrap())
+        }
+
+        fn write(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn matches_globs() {
+        let cases = [
+            ("*.md", "README.md", true),
+            ("*.md", "docs/README.md", false),
+            ("docs/*.md", "docs/guide.md", true),
+            ("docs/*.md", "docs/api/guide.md", false),
+            ("docs/**/*.md", "docs/guide.md", true),
+            ("docs/**/*.md", "docs/api/v2/guide.md", true),
+            ("**", "any/path/at/all", true),
+            ("file?.txt", "file1.txt", true),
+            ("file?.txt", "file10.txt", false),
+            ("*.{md,rst}", "notes.rst", true),
+            ("*.{md,rst}", "notes.txt", false),
+            ("a+b(c).md", "a+b(c).md", true),
+            // A glob matching a directory matches everything inside it
+            ("build", "build/out/log.txt", true),
+            ("build", "builder", false),
+        ];
+        for (glob, path, matches) in cases {
+            let regex = glob_regex(glob).unwrap();
+            assert_eq!(regex.is_match(path), matches, "{:?} on {:?}", glob, path);
+        }
+        assert_eq!(glob_pattern("{a,b"), Err("unclosed `{`"));
+        assert_eq!(glob_pattern("a}"), Err("unmatched `}`"));
+    }
+
+    #[test]
+    fn anchors_globs_at_their_config() {
+        let dir = Path::new("/project/docs");
+        assert_eq!(anchor_glob(dir, "*.md"), "/project/docs/**/*.md");
+        assert_eq!(anchor_glob(dir, "/*.md"), "/project/docs/*.md");
+        assert_eq!(anchor_glob(dir, "api/*.md"), "/project/docs/api/*.md");
+    }
+
+    #[test]
+    fn loads_and_resolves_paths() {
+        let tree = TempTree::new("load");
+        let file_path = tree.write(
+            "sub/.spellcheck.toml",
+            "dictionaries = [\"words.txt\"]\n[html]\nskip-elements = [\"kbd\"]\n",
+        );
+        let config = Config::load(&file_path).unwrap();
+        assert_eq!(
+            config.dictionaries,
+            [tree.0.join("sub/words.txt").to_string_lossy()]
+        );
+        assert_eq!(config.html.skip_elements, Some(vec!["kbd".to_string()]));
+
+        for contents in [
+            "exclude = [\"{a,b\"]",
+            "[checker]\ntokenizer = \"nope\"",
+            "[html]\nskip_elements = [\"kbd\"]",
+        ] {
+            tree.write("bad/.spellcheck.toml", contents);
+            let e = Config::load(&tree.0.join("bad/.spellcheck.toml")).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert!(e.to_string().contains(".spellcheck.toml"), "{}", e);
+        }
+    }
+
+    #[test]
+    fn discovers_configs_up_to_the_root() {
+        let tree = TempTree::new("discover");
+        tree.write(".spellcheck.toml", "ignore-words = [\"outside\"]");
+        tree.write(
+            "project/.spellcheck.toml",
+            "root = true\nignore-words = [\"project\"]\ninclude = [\"*.md\"]\n\
+             exclude = [\"build\"]\n[checker]\ntop-k = 5\ndistance = 1\n",
+        );
+        tree.write(
+            "project/docs/.spellcheck.toml",
+            "ignore-words = [\"docs\"]\nexclude = [\"drafts\"]\n[checker]\ntop-k = 7\n",
+        );
+        let guide = tree.write("project/docs/guide.md", "");
+        let notes = tree.write("project/docs/notes.txt", "");
+        let draft = tree.write("project/docs/drafts/todo.md", "");
+        let built = tree.write("project/build/out.md", "");
+        let readme = tree.write("project/README.md", "");
+
+        let config = Config::discover(&guide).unwrap();
+        assert!(config.root);
+        assert_eq!(config.ignore_words, ["project", "docs"]);
+        assert_eq!(config.checker.top_k, Some(7));
+        assert_eq!(config.checker.distance, Some(1));
+        assert!(config.includes(&guide));
+        assert!(config.includes(&readme));
+        assert!(!config.includes(&notes));
+        assert!(!config.includes(&draft));
+        assert!(!config.includes(&built));
+        assert!(config.excludes_dir(&tree.0.join("project/build")));
+
+        let config = Config::discover(&readme).unwrap();
+        assert_eq!(config.ignore_words, ["project"]);
+        assert!(config.includes(&draft));
+    }
+
+    #[test]
+    fn inner_configs_override_outer_ones() {
+        let outer: Config = toml::from_str(
+            "dictionaries = [\"a\"]\npersonal-dictionary = \"outer\"\n\
+             include = [\"*.md\"]\nexclude = [\"x\"]\n[frontends]\ntxt = \"markdown\"\n\
+             [checker]\ntop-k = 5\ntokenizer = \"unicode\"\n[filter]\nurls = false\n\
+             [html]\nskip-elements = [\"pre\"]\n",
+        )
+        .unwrap();
+        let inner: Config = toml::from_str(
+            "dictionaries = [\"b\"]\ninclude = [\"*.rst\"]\nexclude = [\"y\"]\n\
+             [frontends]\ntxt = \"plain\"\n[checker]\ntop-k = 9\n[filter]\nemails = false\n\
+             [html]\ncheck-attributes = [\"title\"]\n",
+        )
+        .unwrap();
+
+        let mut config = outer;
+        config.merge(inner);
+        assert_eq!(config.dictionaries, ["a", "b"]);
+        assert_eq!(config.personal_dictionary.as_deref(), Some("outer"));
+        assert_eq!(config.include, ["*.rst"]);
+        assert_eq!(config.exclude, ["x", "y"]);
+        assert_eq!(config.frontends["txt"], "plain");
+        assert_eq!(config.checker.top_k, Some(9));
+        assert_eq!(config.checker.tokenizer(), "unicode");
+        assert_eq!(
+            (config.filter.urls, config.filter.emails),
+            (Some(false), Some(false))
+        );
+        assert_eq!(config.html.skip_elements, Some(vec!["pre".to_string()]));
+        assert_eq!(
+            config.html.check_attributes,
+            Some(vec!["title".to_string()])
+        );
+    }
+}
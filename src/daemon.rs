@@ -0,0 +1,288 @@
+use crate::utils::io::Finding;
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::fs::Permissions;
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+// Larger frames are refused rather than allocated
+const MAX_FRAME_SIZE: usize = 64 << 20;
+
+/// `$XDG_RUNTIME_DIR/rust-spell-checker.sock`, or a per-user socket in the temporary
+/// directory.
+pub fn default_socket_path() -> String {
+    let path = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("rust-spell-checker.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_default();
+            std::env::temp_dir().join(format!("rust-spell-checker-{}.sock", user))
+        }
+    };
+    path.to_string_lossy().into_owned()
+}
+
+/// A request to the daemon, answered only when `fingerprint` matches the
+/// configuration the daemon was started with (see `Config::fingerprint`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub fingerprint: String,
+    #[serde(flatten)]
+    pub command: DaemonCommand,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum DaemonCommand {
+    /// Check a file, read by the daemon, with the given front-end
+    Check {
+        file: String,
+        frontend: String,
+    },
+    Suggest {
+        word: String,
+    },
+    /// Answered whatever the fingerprint
+    Stop,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Response {
+    Findings {
+        findings: Vec<Finding>,
+    },
+    Suggestions {
+        correct: bool,
+        suggestions: Vec<String>,
+    },
+    /// The daemon checks with another configuration
+    Mismatch,
+    Stopping,
+    Error {
+        message: String,
+    },
+}
+
+/// Background process keeping a loaded checker, answering requests on a Unix socket.
+///
+/// Messages in both directions are JSON, prefixed with their length as a 4-byte
+/// big-endian integer. A connection may carry any number of requests and is answered on
+/// its own thread, as clients keep it open between requests.
+pub struct Daemon {
+    socket_path: String,
+    fingerprint: String,
+    stopping: AtomicBool,
+}
+
+impl Daemon {
+    pub fn new(socket_path: &str, fingerprint: String) -> Self {
+        Daemon {
+            socket_path: socket_path.to_string(),
+            fingerprint,
+            stopping: AtomicBool::new(false),
+        }
+    }
+
+    /// Binds the socket, replacing the file of a daemon that did not remove it. Only its
+    /// owner may connect, since requests read files with the daemon's permissions.
+    pub fn bind(&self) -> io::Result<UnixListener> {
+        if UnixStream::connect(&self.socket_path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("a daemon is already listening on {}", self.socket_path),
+            ));
+        }
+        match fs::remove_file(&self.socket_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        let listener = UnixListener::bind(&self.socket_path)?;
+        fs::set_permissions(&self.socket_path, Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    /// Answers connections until a `stop` request, then removes the socket.
+    ///
+    /// Connections block on reads between requests, so each gets a thread of its own
+    /// and the rayon pool is left to the checks.
+    pub fn serve(
+        &self,
+        listener: UnixListener,
+        handle: impl Fn(DaemonCommand) -> Response + Sync,
+    ) -> io::Result<()> {
+        info!("Listening on {}", self.socket_path);
+        let handle = &handle;
+        // Open connections, closed for reading on stop so that idle clients do not keep
+        // the daemon running
+        let connections = Mutex::new(HashMap::new());
+        let connections = &connections;
+        std::thread::scope(|scope| {
+            for (id, stream) in listener.incoming().enumerate() {
+                if self.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                let stream = match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                    Ok((clone, stream)) => {
+                        connections.lock().unwrap().insert(id, clone);
+                        stream
+                    }
+                    Err(e) => {
+                        error!("Connection failed: {}", e);
+                        continue;
+                    }
+                };
+                scope.spawn(move || {
+                    if let Err(e) = self.answer(stream, handle) {
+                        debug!("Connection closed: {}", e);
+                    }
+                    connections.lock().unwrap().remove(&id);
+                });
+            }
+            // Requests being answered still get their response
+            for stream in connections.lock().unwrap().values() {
+                let _ = stream.shutdown(Shutdown::Read);
+            }
+        });
+        info!("Daemon stopped");
+        fs::remove_file(&self.socket_path)
+    }
+
+    fn answer(
+        &self,
+        mut stream: UnixStream,
+        handle: &(impl Fn(DaemonCommand) -> Response + Sync),
+    ) -> io::Result<()> {
+        while let Some(request) = read_frame::<Request>(&mut stream)? {
+            let response = match request.command {
+                DaemonCommand::Stop => {
+                    self.stopping.store(true, Ordering::SeqCst);
+                    // Wakes the accepting thread up so that it sees the flag
+                    let _ = UnixStream::connect(&self.socket_path);
+                    Response::Stopping
+                }
+                _ if request.fingerprint != self.fingerprint => Response::Mismatch,
+                command => handle(command),
+            };
+            write_frame(&mut stream, &response)?;
+        }
+        Ok(())
+    }
+}
+
+/// Connection to a running daemon.
+pub struct DaemonClient {
+    stream: UnixStream,
+}
+
+impl DaemonClient {
+    /// None when no daemon listens on `socket_path`.
+    pub fn connect(socket_path: &str) -> Option<Self> {
+        let stream = UnixStream::connect(socket_path).ok()?;
+        debug!("Using the daemon on {}", socket_path);
+        Some(DaemonClient { stream })
+    }
+
+    pub fn request(&mut self, fingerprint: &str, command: DaemonCommand) -> io::Result<Response> {
+        let request = Request {
+            fingerprint: fingerprint.to_string(),
+            command,
+        };
+        write_frame(&mut self.stream, &request)?;
+        read_frame(&mut self.stream)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the daemon closed the connection",
+            )
+        })
+    }
+}
+
+// None when the peer closed the connection between messages
+fn read_frame<T: for<'de> Deserialize<'de>>(stream: &mut UnixStream) -> io::Result<Option<T>> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes", length),
+        ));
+    }
+    let mut message = vec![0; length];
+    stream.read_exact(&mut message)?;
+    serde_json::from_slice(&message)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_frame(stream: &mut UnixStream, message: &impl Serialize) -> io::Result<()> {
+    let message = serde_json::to_vec(message)?;
+    stream.write_all(&(message.len() as u32).to_be_bytes())?;
+    stream.write_all(&message)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn stops_with_idle_clients_connected() {
+        let socket_path = std::env::temp_dir()
+            .join(format!("daemon-{}.sock", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let daemon = Daemon::new(&socket_path, "fingerprint".to_string());
+        let listener = daemon.bind().unwrap();
+        let mode = fs::metadata(&socket_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let served = thread::spawn(move || {
+            daemon.serve(listener, |command| match command {
+                DaemonCommand::Suggest { word } => Response::Suggestions {
+                    correct: false,
+                    suggestions: vec![word.to_uppercase()],
+                },
+                _ => Response::Error {
+                    message: "unexpected".to_string(),
+                },
+            })
+        });
+
+        // More idle clients than the rayon pool has workers
+        let idle = (0..rayon::current_num_threads() + 1)
+            .map(|_| DaemonClient::connect(&socket_path).unwrap())
+            .collect::<Vec<_>>();
+        let mut client = DaemonClient::connect(&socket_path).unwrap();
+        let suggest = |word: &str| DaemonCommand::Suggest {
+            word: word.to_string(),
+        };
+        match client.request("fingerprint", suggest("word")).unwrap() {
+            Response::Suggestions { suggestions, .. } => assert_eq!(suggestions, ["WORD"]),
+            response => panic!("{:?}", response),
+        }
+        assert!(matches!(
+            client.request("other", suggest("word")).unwrap(),
+            Response::Mismatch
+        ));
+        assert!(matches!(
+            client.request("other", DaemonCommand::Stop).unwrap(),
+            Response::Stopping
+        ));
+
+        served.join().unwrap().unwrap();
+        assert!(!std::path::Path::new(&socket_path).exists());
+        drop(idle);
+    }
+}
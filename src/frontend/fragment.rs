@@ -0,0 +1,241 @@
+use crate::frontend::html::HtmlExtractor;
+use crate::frontend::latex::LatexExtractor;
+use crate::frontend::markdown::MarkdownExtractor;
+use crate::frontend::source_code::{Language, SourceCodeExtractor};
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::{LineIndex, Token, Tokenizer};
+use std::path::Path;
+
+/// Human-language text extracted from a document, with a mapping back to the source.
+pub struct Fragment {
+    pub text: String,
+    segments: Vec<Segment>,
+}
+
+// A piece of fragment text: either copied verbatim from the source, or replacing a
+// source span (decoded entities, gaps left by skipped markup)
+struct Segment {
+    text_offset: usize,
+    source_start: usize,
+    source_end: usize,
+    verbatim: bool,
+}
+
+impl Fragment {
+    pub fn verbatim(source: &str, start: usize, end: usize) -> Self {
+        let mut builder = FragmentBuilder::new();
+        builder.push(start, &source[start..end]);
+        builder.fragment
+    }
+
+    /// Source byte offset where the text starting at `offset` comes from.
+    pub fn source_start(&self, offset: usize) -> usize {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.text_offset <= offset);
+        match self.segments.get(index.wrapping_sub(1)) {
+            Some(segment) if segment.verbatim => {
+                segment.source_start + offset - segment.text_offset
+            }
+            Some(segment) => segment.source_start,
+            None => 0,
+        }
+    }
+
+    /// Source byte offset where the text ending at `offset` comes from.
+    pub fn source_end(&self, offset: usize) -> usize {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.text_offset < offset);
+        match self.segments.get(index.wrapping_sub(1)) {
+            Some(segment) if segment.verbatim => {
+                segment.source_start + offset - segment.text_offset
+            }
+            Some(segment) => segment.source_end,
+            None => self.source_start(offset),
+        }
+    }
+}
+
+/// Builds a fragment out of source slices, replacement text and gaps.
+pub struct FragmentBuilder {
+    fragment: Fragment,
+}
+
+impl FragmentBuilder {
+    pub fn new() -> Self {
+        FragmentBuilder {
+            fragment: Fragment {
+                text: String::new(),
+                segments: Vec::new(),
+            },
+        }
+    }
+
+    /// Appends `text`, copied verbatim from `source_offset` in the source.
+    pub fn push(&mut self, source_offset: usize, text: &str) {
+        if !text.is_empty() {
+            self.push_segment(source_offset, source_offset + text.len(), text, true);
+        }
+    }
+
+    /// Appends `text` in place of the source span `source_start..source_end`.
+    pub fn push_replacement(&mut self, source_start: usize, source_end: usize, text: &str) {
+        self.push_segment(source_start, source_end, text, false);
+    }
+
+    /// Separates the words on both sides of the skipped source span.
+    pub fn push_gap(&mut self, source_start: usize, source_end: usize) {
+        if !self.fragment.text.is_empty() && !self.fragment.text.ends_with(' ') {
+            self.push_replacement(source_start, source_end, " ");
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragment.text.trim().is_empty()
+    }
+
+    /// The finished fragment, or `None` if it holds no text.
+    pub fn finish(self) -> Option<Fragment> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.fragment)
+        }
+    }
+
+    fn push_segment(&mut self, source_start: usize, source_end: usize, text: &str, verbatim: bool) {
+        self.fragment.segments.push(Segment {
+            text_offset: self.fragment.text.len(),
+            source_start,
+            source_end,
+            verbatim,
+        });
+        self.fragment.text.push_str(text);
+    }
+}
+
+impl Default for FragmentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pulls the text worth spell checking out of a document format.
+pub trait Extractor: Sync {
+    fn extract(&self, source: &str) -> Vec<Fragment>;
+}
+
+/// The whole input is prose.
+pub struct PlainTextExtractor;
+
+impl Extractor for PlainTextExtractor {
+    fn extract(&self, source: &str) -> Vec<Fragment> {
+        vec![Fragment::verbatim(source, 0, source.len())]
+    }
+}
+
+pub const FRONTEND_NAMES: &[&str] = &[
+    "plain",
+    "markdown",
+    "latex",
+    "html",
+    "rust",
+    "python",
+    "javascript",
+    "go",
+    "c",
+    "shell",
+];
+
+pub fn extractor_for_path(path: &str) -> Box<dyn Extractor> {
+    extractor_by_name(frontend_for_path(path)).unwrap()
+}
+
+/// Looks up a front-end by name, see `FRONTEND_NAMES`.
+pub fn extractor_by_name(name: &str) -> Option<Box<dyn Extractor>> {
+    let extractor: Box<dyn Extractor> = match name {
+        "plain" => Box::new(PlainTextExtractor),
+        "markdown" => Box::new(MarkdownExtractor::new()),
+        "latex" => Box::new(LatexExtractor),
+        "html" => Box::new(HtmlExtractor::new()),
+        language => Box::new(SourceCodeExtractor::new(Language::from_name(language)?)),
+    };
+    Some(extractor)
+}
+
+/// Name of the front-end picked from the extension of `path`, `plain` if none fits.
+pub fn frontend_for_path(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "md" | "markdown" | "mdown" | "mkd" => "markdown",
+        "tex" | "ltx" | "latex" => "latex",
+        "html" | "htm" | "xhtml" | "xml" | "svg" | "resx" | "xlf" | "xliff" => "html",
+        extension => Language::from_extension(extension).map_or("plain", Language::name),
+    }
+}
+
+/// Whether `frontend` checks plain text, which can be streamed in chunks since no
+/// markup spans across lines.
+pub fn is_plain_text(frontend: &str) -> bool {
+    frontend == "plain"
+}
+
+/// Tokenizes every fragment, keeping the words borrowed from the fragment text but
+/// positioned in the original `source`.
+pub fn tokenize_fragments<'a>(
+    source: &str,
+    fragments: &'a [Fragment],
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+) -> Vec<Token<'a>> {
+    let line_index = LineIndex::new(source);
+
+    fragments
+        .iter()
+        .flat_map(|fragment| {
+            let tokens = filter.filter(&fragment.text, tokenizer.tokens(&fragment.text));
+            tokens.map(|token| {
+                let start = fragment.source_start(token.range.start);
+                let end = fragment.source_end(token.range.end);
+                let (line, column) = line_index.position(source, start);
+                Token {
+                    text: token.text,
+                    range: start..end,
+                    line,
+                    column,
+                }
+            })
+        })
+        .collect()
+}
+
+/// The words `extractor` finds in `source`, with their 1-based line and column and the
+/// source text they map back to.
+#[cfg(test)]
+pub fn extracted_words(
+    extractor: &dyn Extractor,
+    source: &str,
+) -> Vec<(String, usize, usize, String)> {
+    use crate::utils::token_filter::TokenFilterOptions;
+    use crate::utils::tokenizer::UnicodeWordTokenizer;
+
+    let fragments = extractor.extract(source);
+    let filter = TokenFilter::new(TokenFilterOptions::default());
+    tokenize_fragments(source, &fragments, &UnicodeWordTokenizer, &filter)
+        .into_iter()
+        .map(|token| {
+            (
+                token.text.to_string(),
+                token.line,
+                token.column,
+                source[token.range].to_string(),
+            )
+        })
+        .collect()
+}
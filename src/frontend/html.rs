@@ -0,0 +1,352 @@
+use crate::frontend::fragment::{Extractor, Fragment, FragmentBuilder};
+use crate::utils::unicode_normalization::compose;
+use regex::Regex;
+
+pub const DEFAULT_SKIPPED_ELEMENTS: &[&str] = &["script", "style", "code", "pre"];
+pub const DEFAULT_CHECKED_ATTRIBUTES: &[&str] = &["alt", "title"];
+
+// Elements that sit inside words often enough that they must not split them
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "em", "i", "mark", "s", "small", "span", "strong", "sub", "sup", "u",
+];
+
+/// Extracts text from HTML and XML: tags are stripped, entities decoded, and
+/// human-readable attribute values checked alongside the text.
+pub struct HtmlExtractor {
+    skipped_elements: Vec<String>,
+    checked_attributes: Vec<String>,
+    attribute_pattern: Regex,
+    entity_pattern: Regex,
+}
+
+impl HtmlExtractor {
+    pub fn new() -> Self {
+        HtmlExtractor {
+            skipped_elements: DEFAULT_SKIPPED_ELEMENTS
+                .iter()
+                .map(|element| element.to_string())
+                .collect(),
+            checked_attributes: DEFAULT_CHECKED_ATTRIBUTES
+                .iter()
+                .map(|attribute| attribute.to_string())
+                .collect(),
+            attribute_pattern: Regex::new(
+                r#"([^\s=/>]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+)))?"#,
+            )
+            .unwrap(),
+            entity_pattern: Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z][A-Za-z0-9]*);")
+                .unwrap(),
+        }
+    }
+
+    /// Elements whose whole content is skipped, `script`, `style`, `code` and `pre` by default.
+    pub fn with_skipped_elements(mut self, elements: &[String]) -> Self {
+        self.skipped_elements = elements
+            .iter()
+            .map(|element| element.to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Attributes whose values are checked, `alt` and `title` by default.
+    pub fn with_checked_attributes(mut self, attributes: &[String]) -> Self {
+        self.checked_attributes = attributes
+            .iter()
+            .map(|attribute| attribute.to_lowercase())
+            .collect();
+        self
+    }
+
+    // Text with entities decoded, each decoded entity replacing its source span
+    fn push_decoded(&self, builder: &mut FragmentBuilder, source: &str, start: usize, end: usize) {
+        let mut kept = start;
+        for entity in self.entity_pattern.captures_iter(&source[start..end]) {
+            let whole = entity.get(0).unwrap();
+            let (entity_start, entity_end) = (start + whole.start(), start + whole.end());
+            builder.push(kept, &source[kept..entity_start]);
+            match decode_entity(&entity[1]) {
+                Some(decoded) => {
+                    builder.push_replacement(entity_start, entity_end, &decoded.to_string())
+                }
+                None => builder.push_gap(entity_start, entity_end),
+            }
+            kept = entity_end;
+        }
+        builder.push(kept, &source[kept..end]);
+    }
+
+    fn push_attributes(
+        &self,
+        builder: &mut FragmentBuilder,
+        source: &str,
+        start: usize,
+        end: usize,
+    ) {
+        for attribute in self.attribute_pattern.captures_iter(&source[start..end]) {
+            let name = attribute[1].to_lowercase();
+            let value = attribute.get(2).or(attribute.get(3)).or(attribute.get(4));
+            if let Some(value) = value.filter(|_| self.checked_attributes.contains(&name)) {
+                builder.push_gap(start + value.start(), start + value.start());
+                self.push_decoded(builder, source, start + value.start(), start + value.end());
+                builder.push_gap(start + value.end(), start + value.end());
+            }
+        }
+    }
+}
+
+impl Default for HtmlExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extractor for HtmlExtractor {
+    fn extract(&self, source: &str) -> Vec<Fragment> {
+        let mut builder = FragmentBuilder::new();
+        let mut text_start = 0;
+        let mut i = 0;
+
+        while let Some(tag_offset) = source[i..].find('<') {
+            let tag_start = i + tag_offset;
+            self.push_decoded(&mut builder, source, text_start, tag_start);
+
+            let rest = &source[tag_start..];
+            let tag_end = if rest.starts_with("<!--") {
+                let comment_end = rest
+                    .find("-->")
+                    .map_or(source.len(), |close| tag_start + close + 3);
+                builder.push_gap(tag_start, comment_end);
+                comment_end
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let cdata_start = tag_start + "<![CDATA[".len();
+                let cdata_end = cdata
+                    .find("]]>")
+                    .map_or(source.len(), |close| cdata_start + close);
+                self.push_decoded(&mut builder, source, cdata_start, cdata_end);
+                (cdata_end + 3).min(source.len())
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                let declaration_end = rest
+                    .find('>')
+                    .map_or(source.len(), |close| tag_start + close + 1);
+                builder.push_gap(tag_start, declaration_end);
+                declaration_end
+            } else if let Some(tag) = parse_tag(source, tag_start) {
+                if !INLINE_ELEMENTS.contains(&tag.name.as_str()) {
+                    builder.push_gap(tag_start, tag.end);
+                }
+                self.push_attributes(&mut builder, source, tag.attributes_start, tag.end);
+
+                if !tag.closing && !tag.self_closing && self.skipped_elements.contains(&tag.name) {
+                    let skipped_end = find_closing_tag(source, tag.end, &tag.name);
+                    builder.push_gap(tag.end, skipped_end);
+                    skipped_end
+                } else {
+                    tag.end
+                }
+            } else {
+                // A lone `<` in text
+                i = tag_start + 1;
+                continue;
+            };
+
+            i = tag_end;
+            text_start = tag_end;
+        }
+
+        self.push_decoded(&mut builder, source, text_start, source.len());
+        builder.finish().into_iter().collect()
+    }
+}
+
+struct Tag {
+    name: String,
+    attributes_start: usize,
+    end: usize,
+    closing: bool,
+    self_closing: bool,
+}
+
+fn parse_tag(source: &str, start: usize) -> Option<Tag> {
+    let bytes = source.as_bytes();
+    let closing = bytes.get(start + 1) == Some(&b'/');
+    let name_start = start + 1 + closing as usize;
+    let name_len = source[name_start..]
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(source.len() - name_start);
+    if name_len == 0 || !bytes[name_start].is_ascii_alphabetic() {
+        return None;
+    }
+
+    // Find the closing `>`, ignoring any inside quoted attribute values
+    let mut quote = None;
+    let mut i = name_start + name_len;
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (Some(open), b) if b == open => quote = None,
+            (None, b'"') | (None, b'\'') => quote = Some(bytes[i]),
+            (None, b'>') => break,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Some(Tag {
+        name: source[name_start..name_start + name_len].to_lowercase(),
+        attributes_start: name_start + name_len,
+        end: (i + 1).min(source.len()),
+        closing,
+        self_closing: i > 0 && bytes.get(i - 1) == Some(&b'/'),
+    })
+}
+
+// Offset just past `</name>`, or the end of the source if the element is never closed
+fn find_closing_tag(source: &str, from: usize, name: &str) -> usize {
+    let lowercase = source[from..].to_ascii_lowercase();
+    let closing = format!("</{}", name);
+    match lowercase.find(&closing) {
+        Some(offset) => {
+            let close_start = from + offset;
+            source[close_start..]
+                .find('>')
+                .map_or(source.len(), |end| close_start + end + 1)
+        }
+        None => source.len(),
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let decoded = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" | "rsquo" | "lsquo" => '\'',
+        "nbsp" | "ensp" | "emsp" | "thinsp" => ' ',
+        "ldquo" | "rdquo" => '"',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "shy" => '\u{ad}',
+        _ => return decode_accented_entity(entity),
+    };
+    Some(decoded)
+}
+
+// Latin-1 letters such as `&eacute;` and `&Uuml;`
+fn decode_accented_entity(entity: &str) -> Option<char> {
+    let decoded = match entity {
+        "szlig" => 'ß',
+        "aelig" => 'æ',
+        "AElig" => 'Æ',
+        "oslash" => 'ø',
+        "Oslash" => 'Ø',
+        _ => {
+            let letter = entity.chars().next()?;
+            return accented_letter(letter, &entity[letter.len_utf8()..]);
+        }
+    };
+    Some(decoded)
+}
+
+/// `letter` with the accent named as in HTML entities (`acute`, `grave`, `circ`,
+/// `uml`, `tilde`, `cedil`, `ring`).
+pub fn accented_letter(letter: char, accent: &str) -> Option<char> {
+    let mark = match accent {
+        "grave" => '\u{0300}',
+        "acute" => '\u{0301}',
+        "circ" => '\u{0302}',
+        "tilde" => '\u{0303}',
+        "uml" => '\u{0308}',
+        "ring" => '\u{030a}',
+        "cedil" => '\u{0327}',
+        _ => return None,
+    };
+    compose(letter, mark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::fragment::extracted_words;
+
+    fn words(extractor: &HtmlExtractor, source: &str) -> Vec<(String, usize, usize, String)> {
+        extracted_words(extractor, source)
+    }
+
+    fn word(text: &str, line: usize, column: usize) -> (String, usize, usize, String) {
+        (text.to_string(), line, column, text.to_string())
+    }
+
+    #[test]
+    fn skips_tags_and_skipped_elements() {
+        let source = "<!DOCTYPE html><p class=\"hidn\">Frst <em>emph</em></p>\n\
+                      <script>var scrpt = \"</p>\";</script><pre>\nprfmt\n</PRE>\n\
+                      <!-- cmnt --><div>lst</div>";
+        assert_eq!(
+            words(&HtmlExtractor::new(), source),
+            [word("Frst", 1, 32), word("emph", 1, 41), word("lst", 5, 19),]
+        );
+    }
+
+    #[test]
+    fn checks_attribute_values() {
+        let source =
+            "<img src=\"pic.png\" alt=\"Imge txt\" title='Tltle'><a title=unqtd href=x>lnk</a>";
+        assert_eq!(
+            words(&HtmlExtractor::new(), source),
+            [
+                word("Imge", 1, 25),
+                word("txt", 1, 30),
+                word("Tltle", 1, 42),
+                word("unqtd", 1, 58),
+                word("lnk", 1, 71),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_entities_to_their_source() {
+        let source = "caf&eacute; &amp; na&iuml;ve &#233;t&#xE9; &bogus; d&rsquo;or";
+        assert_eq!(
+            words(&HtmlExtractor::new(), source),
+            [
+                ("café".to_string(), 1, 1, "caf&eacute;".to_string()),
+                ("naïve".to_string(), 1, 19, "na&iuml;ve".to_string()),
+                ("été".to_string(), 1, 30, "&#233;t&#xE9;".to_string()),
+                ("d'or".to_string(), 1, 52, "d&rsquo;or".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_words_across_inline_tags() {
+        let source = "<p>wo<b>rd</b> <![CDATA[cdta]]></p>";
+        assert_eq!(
+            words(&HtmlExtractor::new(), source),
+            [
+                ("word".to_string(), 1, 4, "wo<b>rd".to_string()),
+                word("cdta", 1, 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn configures_elements_and_attributes() {
+        let extractor = HtmlExtractor::new()
+            .with_skipped_elements(&["Note".to_string()])
+            .with_checked_attributes(&["Label".to_string()]);
+        let source = "<note>hidn</note><code>shwn</code><x label=\"lbl\" alt=\"alt\"/>";
+        assert_eq!(
+            words(&extractor, source),
+            [word("shwn", 1, 24), word("lbl", 1, 45)]
+        );
+    }
+}
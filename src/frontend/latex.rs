@@ -0,0 +1,386 @@
+use crate::frontend::fragment::{Extractor, Fragment, FragmentBuilder};
+use crate::frontend::html::accented_letter;
+
+// Commands whose arguments are keys, paths or code rather than text, with the number
+// of braced arguments to skip; text arguments such as `\href`'s second stay checked
+const KEY_COMMANDS: &[(&str, usize)] = &[
+    ("cite", 1),
+    ("citep", 1),
+    ("citet", 1),
+    ("citeauthor", 1),
+    ("citeyear", 1),
+    ("nocite", 1),
+    ("ref", 1),
+    ("eqref", 1),
+    ("pageref", 1),
+    ("autoref", 1),
+    ("cref", 1),
+    ("Cref", 1),
+    ("label", 1),
+    ("url", 1),
+    ("href", 1),
+    ("include", 1),
+    ("input", 1),
+    ("includegraphics", 1),
+    ("lstinputlisting", 1),
+    ("usepackage", 1),
+    ("RequirePackage", 1),
+    ("documentclass", 1),
+    ("bibliography", 1),
+    ("bibliographystyle", 1),
+    ("graphicspath", 1),
+    ("pagestyle", 1),
+    ("thispagestyle", 1),
+    ("pagenumbering", 1),
+    ("hspace", 1),
+    ("vspace", 1),
+    ("color", 1),
+    ("textcolor", 1),
+    ("fontsize", 2),
+    ("setlength", 2),
+    ("addtolength", 2),
+    ("setcounter", 2),
+    ("newcommand", 2),
+    ("renewcommand", 2),
+    ("providecommand", 2),
+    ("definecolor", 3),
+    ("newenvironment", 3),
+    ("renewenvironment", 3),
+];
+
+// Environments holding math, code or drawings, skipped up to their `\end`
+const SKIPPED_ENVIRONMENTS: &[&str] = &[
+    "verbatim",
+    "Verbatim",
+    "lstlisting",
+    "minted",
+    "comment",
+    "equation",
+    "align",
+    "alignat",
+    "flalign",
+    "gather",
+    "multline",
+    "eqnarray",
+    "displaymath",
+    "math",
+    "tikzpicture",
+];
+
+// Environments whose first braced argument is a specification, such as `{lcr}`
+const SPEC_ENVIRONMENTS: &[&str] = &["tabular", "tabularx", "array", "longtable", "minipage"];
+
+/// Extracts prose from LaTeX: command names, keys (`\cite`, `\ref`, `\label`), math and
+/// verbatim environments are skipped, text arguments (`\section{...}`, `\emph{...}`)
+/// are checked, and accent commands such as `\'e` are decoded.
+pub struct LatexExtractor;
+
+impl Extractor for LatexExtractor {
+    fn extract(&self, source: &str) -> Vec<Fragment> {
+        let bytes = source.as_bytes();
+        let mut builder = FragmentBuilder::new();
+        let mut kept = 0;
+        let mut i = 0;
+
+        // Only ASCII bytes are matched, so every slice boundary is a char boundary
+        while i < bytes.len() {
+            let skipped_until = match bytes[i] {
+                b'%' => Some(find_from(source, i, "\n").unwrap_or(source.len())),
+                b'$' if source[i..].starts_with("$$") => {
+                    Some(find_from(source, i + 2, "$$").map_or(source.len(), |end| end + 2))
+                }
+                b'$' => {
+                    Some(find_unescaped_dollar(source, i + 1).map_or(source.len(), |end| end + 1))
+                }
+                // Grouping braces, dropped without separating the text around them
+                b'{' | b'}' => {
+                    builder.push(kept, &source[kept..i]);
+                    i += 1;
+                    kept = i;
+                    continue;
+                }
+                // `\-` hyphenation hints sit inside words
+                b'\\' if bytes.get(i + 1) == Some(&b'-') => {
+                    builder.push(kept, &source[kept..i]);
+                    i += 2;
+                    kept = i;
+                    continue;
+                }
+                b'\\' => {
+                    let (end, replacement) = command(source, i);
+                    if let Some(replacement) = replacement {
+                        builder.push(kept, &source[kept..i]);
+                        builder.push_replacement(i, end, &replacement.to_string());
+                        i = end;
+                        kept = i;
+                        continue;
+                    }
+                    Some(end)
+                }
+                _ => None,
+            };
+
+            match skipped_until {
+                Some(end) => {
+                    builder.push(kept, &source[kept..i]);
+                    builder.push_gap(i, end);
+                    i = end;
+                    kept = i;
+                }
+                None => i += 1,
+            }
+        }
+
+        builder.push(kept, &source[kept..]);
+        builder.finish().into_iter().collect()
+    }
+}
+
+// End of the command starting at `start`, and the letter it stands for if it is an accent
+fn command(source: &str, start: usize) -> (usize, Option<char>) {
+    let bytes = source.as_bytes();
+    let name_start = start + 1;
+    let Some(&first) = bytes.get(name_start) else {
+        return (source.len(), None);
+    };
+
+    if !first.is_ascii_alphabetic() {
+        return match first {
+            b'(' => (
+                find_from(source, name_start, "\\)").map_or(source.len(), |end| end + 2),
+                None,
+            ),
+            b'[' => (
+                find_from(source, name_start, "\\]").map_or(source.len(), |end| end + 2),
+                None,
+            ),
+            b'\'' | b'`' | b'^' | b'"' | b'~' => accent(source, name_start + 1, first as char),
+            _ => (
+                name_start
+                    + source[name_start..]
+                        .chars()
+                        .next()
+                        .map_or(0, char::len_utf8),
+                None,
+            ),
+        };
+    }
+
+    let name_len = bytes[name_start..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphabetic())
+        .count();
+    let name = &source[name_start..name_start + name_len];
+    let mut end = name_start + name_len;
+    if bytes.get(end) == Some(&b'*') {
+        end += 1;
+    }
+
+    match name {
+        "begin" => {
+            let (environment, after) = braced_argument(source, end);
+            let environment = environment.trim_end_matches('*');
+            if SKIPPED_ENVIRONMENTS.contains(&environment) {
+                let closing = format!("\\end{{{}", environment);
+                let environment_end = find_from(source, after, &closing)
+                    .and_then(|close| find_from(source, close, "}"))
+                    .map_or(source.len(), |close| close + 1);
+                (environment_end, None)
+            } else if SPEC_ENVIRONMENTS.contains(&environment) {
+                (skip_arguments(source, after, 1), None)
+            } else {
+                (after, None)
+            }
+        }
+        "end" => (braced_argument(source, end).1, None),
+        "verb" => {
+            let delimiter = source[end..].chars().next().map_or(0, char::len_utf8);
+            let body = end + delimiter;
+            let close = find_from(source, body, &source[end..body]).unwrap_or(source.len());
+            ((close + delimiter).min(source.len()), None)
+        }
+        // Spaces after a control word only end its name, as in `Stra\ss e`
+        "ss" => (
+            end + source[end..].len() - source[end..].trim_start_matches(' ').len(),
+            Some('ß'),
+        ),
+        "c" => accent(source, end, 'c'),
+        _ => match KEY_COMMANDS.iter().find(|(command, _)| *command == name) {
+            Some(&(_, arguments)) => (skip_arguments(source, end, arguments), None),
+            None => (end, None),
+        },
+    }
+}
+
+// `\'e`, `\'{e}` and `\c{c}` style accents
+fn accent(source: &str, start: usize, accent: char) -> (usize, Option<char>) {
+    let (letter, end) = match source[start..].strip_prefix('{') {
+        Some(braced) => {
+            let letter = braced.chars().next();
+            let end = braced
+                .find('}')
+                .map_or(source.len(), |close| start + 1 + close + 1);
+            (letter, end)
+        }
+        None => {
+            let letter = source[start..].chars().next();
+            (letter, start + letter.map_or(0, char::len_utf8))
+        }
+    };
+
+    let name = match accent {
+        '\'' => "acute",
+        '`' => "grave",
+        '^' => "circ",
+        '"' => "uml",
+        '~' => "tilde",
+        _ => "cedil",
+    };
+    (end, letter.and_then(|letter| accented_letter(letter, name)))
+}
+
+// Skips optional `[...]` arguments and then `count` braced arguments
+fn skip_arguments(source: &str, start: usize, count: usize) -> usize {
+    let mut end = start;
+    for _ in 0..count {
+        end = braced_argument(source, end).1;
+    }
+    end
+}
+
+// Content of the next `{...}` argument, after any optional `[...]` ones, and its end
+fn braced_argument(source: &str, start: usize) -> (&str, usize) {
+    let bytes = source.as_bytes();
+    let mut i = start;
+    loop {
+        while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        match bytes.get(i) {
+            Some(b'[') => i = matching_bracket(bytes, i, b'[', b']'),
+            Some(b'{') => {
+                let end = matching_bracket(bytes, i, b'{', b'}');
+                let content_end = if bytes[end - 1] == b'}' { end - 1 } else { end };
+                return (&source[i + 1..content_end.max(i + 1)], end);
+            }
+            _ => return ("", start),
+        }
+    }
+}
+
+// Offset just past the bracket closing the one at `bytes[start]`
+fn matching_bracket(bytes: &[u8], start: usize, open: u8, close: u8) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == open => depth += 1,
+            b if b == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn find_from(source: &str, start: usize, pattern: &str) -> Option<usize> {
+    source[start..].find(pattern).map(|offset| start + offset)
+}
+
+fn find_unescaped_dollar(source: &str, start: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    (start..bytes.len()).find(|&i| bytes[i] == b'$' && bytes[i - 1] != b'\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::fragment::extracted_words;
+
+    fn words(source: &str) -> Vec<(String, usize, usize, String)> {
+        extracted_words(&LatexExtractor, source)
+    }
+
+    fn word(text: &str, line: usize, column: usize) -> (String, usize, usize, String) {
+        (text.to_string(), line, column, text.to_string())
+    }
+
+    #[test]
+    fn skips_math() {
+        let source = "Let $x = mth$ and \\$5 $$dsply$$ or \\(inln\\) \\[brckt\\] end\n\
+                      \\begin{equation}\nequtn\n\\end{equation} aftr";
+        assert_eq!(
+            words(source),
+            [
+                word("Let", 1, 1),
+                word("and", 1, 15),
+                word("or", 1, 33),
+                word("end", 1, 55),
+                word("aftr", 4, 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_verbatim() {
+        let source = "Txt % a cmnt\n\\verb|vrb txt| and \\verb+x+ \\% prcnt\n\
+                      \\begin{verbatim}\nverbtm\n\\end{verbatim}\ndone";
+        assert_eq!(
+            words(source),
+            [
+                word("Txt", 1, 1),
+                word("and", 2, 16),
+                word("prcnt", 2, 32),
+                word("done", 6, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_text_arguments_but_not_keys() {
+        let source = "\\section{Intrduction} see \\cite[p.~2]{knuth84} and \\ref{fig:one}\n\
+                      \\href{http://x.org}{lnk txt} \\begin{tabular}{lcr} cel \\end{tabular}";
+        assert_eq!(
+            words(source),
+            [
+                word("Intrduction", 1, 10),
+                word("see", 1, 23),
+                word("and", 1, 48),
+                word("lnk", 2, 21),
+                word("txt", 2, 25),
+                word("cel", 2, 51),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_words_across_braces_and_hyphenation_hints() {
+        assert_eq!(
+            words("hy\\-phen {\\em emph}sis"),
+            [
+                ("hyphen".to_string(), 1, 1, "hy\\-phen".to_string()),
+                ("emphsis".to_string(), 1, 15, "emph}sis".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_accents() {
+        let source = "caf\\'e na\\\"{i}ve Stra\\ss e gar\\c{c}on";
+        assert_eq!(
+            words(source),
+            [
+                ("café".to_string(), 1, 1, "caf\\'e".to_string()),
+                ("naïve".to_string(), 1, 8, "na\\\"{i}ve".to_string()),
+                ("Straße".to_string(), 1, 18, "Stra\\ss e".to_string()),
+                ("garçon".to_string(), 1, 28, "gar\\c{c}on".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::frontend::fragment::{Extractor, Fragment, FragmentBuilder};
+use regex::Regex;
+
+/// Extracts prose from Markdown: headings, paragraphs, list items, emphasis, link text
+/// and image alt text. Code blocks, inline code, link destinations, HTML tags and
+/// comments, front matter and link reference definitions are skipped.
+pub struct MarkdownExtractor {
+    // HTML tags and autolinks, anchored at the start of the searched text
+    tag_pattern: Regex,
+    entity_pattern: Regex,
+    link_definition_pattern: Regex,
+    list_item_pattern: Regex,
+}
+
+enum Block {
+    Text,
+    FrontMatter,
+    Fence { marker: char, len: usize },
+    IndentedCode,
+}
+
+impl MarkdownExtractor {
+    pub fn new() -> Self {
+        MarkdownExtractor {
+            tag_pattern: Regex::new(
+                r"^(?:</?[A-Za-z][A-Za-z0-9-]*(?:\s[^>]*)?/?>|<[A-Za-z][A-Za-z0-9+.-]*:[^\s<>]*>|<[^\s<>@]+@[^\s<>]+>)",
+            )
+            .unwrap(),
+            entity_pattern: Regex::new(r"^&(?:#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z][A-Za-z0-9]*);")
+                .unwrap(),
+            link_definition_pattern: Regex::new(r"^ {0,3}\[[^\]]+\]:").unwrap(),
+            list_item_pattern: Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s").unwrap(),
+        }
+    }
+
+    // Inline content of one line, with `in_comment` tracking HTML comments across lines
+    fn extract_inline(
+        &self,
+        source: &str,
+        start: usize,
+        end: usize,
+        in_comment: &mut bool,
+    ) -> Option<Fragment> {
+        let line = &source[start..end];
+        let bytes = line.as_bytes();
+        let mut builder = FragmentBuilder::new();
+        let mut kept = 0;
+        let mut i = 0;
+
+        if *in_comment {
+            let close = line.find("-->")?;
+            *in_comment = false;
+            i = close + 3;
+            kept = i;
+        }
+
+        // Only ASCII bytes are matched, so every slice boundary is a char boundary
+        while i < bytes.len() {
+            let skipped_until = match bytes[i] {
+                b'`' => {
+                    let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                    match find_backtick_run(&line[i + run..], run) {
+                        Some(close) => Some(i + run + close + run),
+                        None => {
+                            i += run;
+                            continue;
+                        }
+                    }
+                }
+                b'<' if line[i..].starts_with("<!--") => match line[i + 4..].find("-->") {
+                    Some(close) => Some(i + 4 + close + 3),
+                    None => {
+                        *in_comment = true;
+                        Some(bytes.len())
+                    }
+                },
+                b'<' => self.tag_pattern.find(&line[i..]).map(|tag| i + tag.end()),
+                b'&' => self
+                    .entity_pattern
+                    .find(&line[i..])
+                    .map(|entity| i + entity.end()),
+                // Link destination `](url)` or reference label `][label]`
+                b']' => match bytes.get(i + 1) {
+                    Some(b'(') => {
+                        find_closing(&bytes[i + 1..], b'(', b')').map(|close| i + 1 + close)
+                    }
+                    Some(b'[') => {
+                        find_closing(&bytes[i + 1..], b'[', b']').map(|close| i + 1 + close)
+                    }
+                    _ => None,
+                },
+                b'\\' if bytes.get(i + 1).is_some_and(u8::is_ascii_punctuation) => {
+                    builder.push(start + kept, &line[kept..i]);
+                    kept = i + 1;
+                    i += 2;
+                    continue;
+                }
+                // Emphasis, as opposed to the underscores inside snake_case words
+                b'_' if !is_intraword(bytes, i) => Some(i + 1),
+                _ => None,
+            };
+
+            match skipped_until {
+                Some(skip_end) => {
+                    builder.push(start + kept, &line[kept..i]);
+                    builder.push_gap(start + i, start + skip_end);
+                    i = skip_end;
+                    kept = i;
+                }
+                None => i += 1,
+            }
+        }
+
+        builder.push(start + kept, &line[kept..]);
+        builder.finish()
+    }
+}
+
+impl Default for MarkdownExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extractor for MarkdownExtractor {
+    fn extract(&self, source: &str) -> Vec<Fragment> {
+        let mut fragments = Vec::new();
+        let mut block = Block::Text;
+        let mut in_comment = false;
+        let mut in_list = false;
+        let mut previous_blank = true;
+        let mut offset = 0;
+
+        for (line_number, line) in source.split_inclusive('\n').enumerate() {
+            let start = offset;
+            offset += line.len();
+            let content = line.trim_end_matches(['\n', '\r']);
+            let blank = content.trim().is_empty();
+            let indented = content.starts_with("    ") || content.starts_with('\t');
+
+            block = match block {
+                Block::FrontMatter => match content.trim_end() {
+                    "---" | "..." => Block::Text,
+                    _ => Block::FrontMatter,
+                },
+                Block::Fence { marker, len } => match fence(content) {
+                    Some((closing, closing_len)) if closing == marker && closing_len >= len => {
+                        Block::Text
+                    }
+                    _ => Block::Fence { marker, len },
+                },
+                Block::IndentedCode if blank || indented => Block::IndentedCode,
+                Block::Text | Block::IndentedCode => {
+                    if line_number == 0 && content.trim_end() == "---" {
+                        Block::FrontMatter
+                    } else if let Some((marker, len)) = fence(content) {
+                        Block::Fence { marker, len }
+                    } else if indented && previous_blank && !in_list && !in_comment {
+                        Block::IndentedCode
+                    } else {
+                        if self.list_item_pattern.is_match(content) {
+                            in_list = true;
+                        } else if !blank && !indented && previous_blank {
+                            in_list = false;
+                        }
+
+                        if !self.link_definition_pattern.is_match(content) {
+                            fragments.extend(self.extract_inline(
+                                source,
+                                start,
+                                start + content.len(),
+                                &mut in_comment,
+                            ));
+                        }
+                        Block::Text
+                    }
+                }
+            };
+
+            previous_blank = blank;
+        }
+
+        fragments
+    }
+}
+
+// Opening or closing code fence: at least three backticks or tildes, indented at most three spaces
+fn fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
+// Offset just past a run of exactly `run` backticks in `text`
+fn find_backtick_run(text: &str, run: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let len = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+            if len == run {
+                return Some(i);
+            }
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+// Offset just past the bracket closing the one at `bytes[0]`
+fn find_closing(bytes: &[u8], open: u8, close: u8) -> Option<usize> {
+    let mut depth = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == open {
+            depth += 1;
+        } else if b == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+fn is_intraword(bytes: &[u8], i: usize) -> bool {
+    let is_word = |b: &u8| b.is_ascii_alphanumeric() || *b >= 0x80;
+    i > 0 && is_word(&bytes[i - 1]) && bytes.get(i + 1).is_some_and(is_word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::fragment::extracted_words;
+
+    // Words with their line and column, checking each maps back to itself in the source
+    fn words(source: &str) -> Vec<(String, usize, usize)> {
+        extracted_words(&MarkdownExtractor::new(), source)
+            .into_iter()
+            .map(|(word, line, column, source_text)| {
+                assert_eq!(word, source_text);
+                (word, line, column)
+            })
+            .collect()
+    }
+
+    fn word(text: &str, line: usize, column: usize) -> (String, usize, usize) {
+        (text.to_string(), line, column)
+    }
+
+    #[test]
+    fn skips_code() {
+        let source = "Some `cde` and ``a ` b`` txt\n\
+                      ```rust\nlet fenced = cde;\n```\n\
+                      ~~~~\n```\nstill cde\n~~~~\n\
+                      \n    indented cde\n\nafter `unclosed";
+        assert_eq!(
+            words(source),
+            [
+                word("Some", 1, 1),
+                word("and", 1, 12),
+                word("txt", 1, 26),
+                word("after", 12, 1),
+                word("unclosed", 12, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_link_destinations() {
+        let source = "See [lnk txt](http://exmple.com/pth) and ![alt txt](img.png),\n\
+                      [labled][ref] or <http://auto.lnk> and <a@b.co>.\n\
+                      \n[ref]: http://defnition";
+        assert_eq!(
+            words(source),
+            [
+                word("See", 1, 1),
+                word("lnk", 1, 6),
+                word("txt", 1, 10),
+                word("and", 1, 38),
+                word("alt", 1, 44),
+                word("txt", 1, 48),
+                word("labled", 2, 2),
+                word("or", 2, 15),
+                word("and", 2, 36),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_markup() {
+        let source = "---\ntitle: Frnt\n---\n\
+                      A <span class=\"clss\">tggd</span> &amp; \\*strs\\* _emph_ snake_case\n\
+                      <!-- hiden\ncoment --> after <!-- x --> end";
+        assert_eq!(
+            words(source),
+            [
+                word("A", 4, 1),
+                word("tggd", 4, 22),
+                word("strs", 4, 42),
+                word("emph", 4, 50),
+                word("snake_case", 4, 56),
+                word("after", 6, 12),
+                word("end", 6, 29),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_list_item_continuations() {
+        let source = "- item wrd\n\n    continud item\n\n    cde blck";
+        assert_eq!(
+            words(source),
+            [
+                word("item", 1, 3),
+                word("wrd", 1, 8),
+                word("continud", 3, 5),
+                word("item", 3, 14),
+                word("cde", 5, 5),
+                word("blck", 5, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        assert_eq!(
+            words("Ça `cödé` fnal"),
+            [word("Ça", 1, 1), word("fnal", 1, 11)]
+        );
+    }
+}
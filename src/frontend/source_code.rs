@@ -0,0 +1,480 @@
+use crate::frontend::fragment::{Extractor, Fragment, FragmentBuilder};
+
+/// Languages whose comments and string literals can be extracted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    C,
+    Shell,
+}
+
+impl Language {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+                Some(Language::JavaScript)
+            }
+            "go" => Some(Language::Go),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "cu" => Some(Language::C),
+            "sh" | "bash" | "zsh" => Some(Language::Shell),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Language::Rust,
+            Language::Python,
+            Language::JavaScript,
+            Language::Go,
+            Language::C,
+            Language::Shell,
+        ]
+        .into_iter()
+        .find(|language| language.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::Go => "go",
+            Language::C => "c",
+            Language::Shell => "shell",
+        }
+    }
+
+    fn syntax(self) -> Syntax {
+        match self {
+            Language::Rust => Syntax {
+                line_comment: "//",
+                block_comment: C_BLOCK_COMMENT,
+                nested_block_comments: true,
+                // `'` also starts lifetimes, and char literals hold no words anyway
+                strings: &[ESCAPED_DOUBLE],
+                raw_strings: true,
+            },
+            Language::Python => Syntax {
+                line_comment: "#",
+                block_comment: None,
+                nested_block_comments: false,
+                strings: PYTHON_STRINGS,
+                raw_strings: false,
+            },
+            Language::JavaScript => Syntax {
+                line_comment: "//",
+                block_comment: C_BLOCK_COMMENT,
+                nested_block_comments: false,
+                strings: JAVASCRIPT_STRINGS,
+                raw_strings: false,
+            },
+            Language::Go => Syntax {
+                line_comment: "//",
+                block_comment: C_BLOCK_COMMENT,
+                nested_block_comments: false,
+                strings: GO_STRINGS,
+                raw_strings: false,
+            },
+            Language::C => Syntax {
+                line_comment: "//",
+                block_comment: C_BLOCK_COMMENT,
+                nested_block_comments: false,
+                strings: &[ESCAPED_DOUBLE, ESCAPED_SINGLE],
+                raw_strings: false,
+            },
+            Language::Shell => Syntax {
+                line_comment: "#",
+                block_comment: None,
+                nested_block_comments: false,
+                strings: SHELL_STRINGS,
+                raw_strings: false,
+            },
+        }
+    }
+}
+
+const C_BLOCK_COMMENT: Option<(&str, &str)> = Some(("/*", "*/"));
+const ESCAPED_DOUBLE: StringSyntax = StringSyntax::new("\"", "\"", true);
+const ESCAPED_SINGLE: StringSyntax = StringSyntax::new("'", "'", true);
+
+const PYTHON_STRINGS: &[StringSyntax] = &[
+    StringSyntax::new("\"\"\"", "\"\"\"", true),
+    StringSyntax::new("'''", "'''", true),
+    ESCAPED_DOUBLE,
+    ESCAPED_SINGLE,
+];
+const JAVASCRIPT_STRINGS: &[StringSyntax] = &[
+    ESCAPED_DOUBLE,
+    ESCAPED_SINGLE,
+    StringSyntax::new("`", "`", true).interpolated("${", "}"),
+];
+const GO_STRINGS: &[StringSyntax] = &[
+    ESCAPED_DOUBLE,
+    ESCAPED_SINGLE,
+    StringSyntax::new("`", "`", false),
+];
+const SHELL_STRINGS: &[StringSyntax] = &[
+    StringSyntax::new("\"", "\"", true).interpolated("$(", ")"),
+    StringSyntax::new("'", "'", false),
+];
+
+struct Syntax {
+    line_comment: &'static str,
+    block_comment: Option<(&'static str, &'static str)>,
+    nested_block_comments: bool,
+    // Checked in order, so longer delimiters sharing a prefix come first
+    strings: &'static [StringSyntax],
+    // Rust `r"..."`, `r#"..."#` and their byte string forms
+    raw_strings: bool,
+}
+
+struct StringSyntax {
+    open: &'static str,
+    close: &'static str,
+    escapes: bool,
+    // Embedded code such as `${...}`, skipped like an escape
+    interpolation: Option<(&'static str, &'static str)>,
+}
+
+impl StringSyntax {
+    const fn new(open: &'static str, close: &'static str, escapes: bool) -> Self {
+        StringSyntax {
+            open,
+            close,
+            escapes,
+            interpolation: None,
+        }
+    }
+
+    const fn interpolated(self, open: &'static str, close: &'static str) -> Self {
+        StringSyntax {
+            interpolation: Some((open, close)),
+            ..self
+        }
+    }
+}
+
+/// Extracts comments, doc comments and string literals from source code.
+pub struct SourceCodeExtractor {
+    language: Language,
+    syntax: Syntax,
+}
+
+impl SourceCodeExtractor {
+    pub fn new(language: Language) -> Self {
+        SourceCodeExtractor {
+            language,
+            syntax: language.syntax(),
+        }
+    }
+
+    // Start and end of the comment body, and where scanning resumes
+    fn block_comment(&self, source: &str, start: usize) -> Option<(usize, usize, usize)> {
+        let (open, close) = self.syntax.block_comment?;
+        if !source[start..].starts_with(open) {
+            return None;
+        }
+
+        // Delimiters are ASCII, so matching on bytes never splits a char
+        let bytes = source.as_bytes();
+        let body_start = start + open.len();
+        let mut depth = 1;
+        let mut i = body_start;
+        while i < bytes.len() {
+            if bytes[i..].starts_with(close.as_bytes()) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((body_start, i, i + close.len()));
+                }
+                i += close.len();
+            } else if self.syntax.nested_block_comments && bytes[i..].starts_with(open.as_bytes()) {
+                depth += 1;
+                i += open.len();
+            } else {
+                i += 1;
+            }
+        }
+        Some((body_start, source.len(), source.len()))
+    }
+
+    fn line_comment(&self, source: &str, start: usize) -> Option<(usize, usize)> {
+        let marker = self.syntax.line_comment;
+        if !source[start..].starts_with(marker) {
+            return None;
+        }
+        // `$#` and `${#var}` are not comments in shell scripts
+        if self.language == Language::Shell
+            && source[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| !c.is_whitespace() && c != ';')
+        {
+            return None;
+        }
+
+        let body_start = start + marker.len();
+        let end = source[body_start..]
+            .find('\n')
+            .map_or(source.len(), |newline| body_start + newline);
+        Some((body_start, end))
+    }
+
+    // `r"..."` / `r#"..."#`, returning the opening and closing delimiters
+    fn raw_string(&self, source: &str, start: usize) -> Option<(usize, String)> {
+        if !self.syntax.raw_strings {
+            return None;
+        }
+        let rest = &source[start..];
+        let prefix = if rest.starts_with("br") {
+            2
+        } else if rest.starts_with('r') {
+            1
+        } else {
+            0
+        };
+        if prefix == 0 || is_identifier_byte(source.as_bytes(), start.wrapping_sub(1)) {
+            return None;
+        }
+
+        let hashes = rest[prefix..].bytes().take_while(|&b| b == b'#').count();
+        if rest.as_bytes().get(prefix + hashes) != Some(&b'"') {
+            return None;
+        }
+        Some((prefix + hashes + 1, format!("\"{}", "#".repeat(hashes))))
+    }
+
+    // Rust char literals, skipped so that `'"'` does not open a string
+    fn char_literal(&self, source: &str, start: usize) -> Option<usize> {
+        if self.language != Language::Rust || !source[start..].starts_with('\'') {
+            return None;
+        }
+        let rest = &source[start + 1..];
+        let literal_len = match rest.strip_prefix('\\') {
+            Some(escaped) => {
+                let escaped_len = escaped.chars().next()?.len_utf8();
+                1 + escaped_len + escaped[escaped_len..].find('\'')?
+            }
+            None => rest.chars().next()?.len_utf8(),
+        };
+        (rest[literal_len..].starts_with('\'') && literal_len <= 12)
+            .then_some(start + literal_len + 2)
+    }
+
+    fn string_literal(
+        &self,
+        source: &str,
+        body_start: usize,
+        close: &str,
+        syntax: Option<&StringSyntax>,
+    ) -> (Option<Fragment>, usize) {
+        let escapes = syntax.is_some_and(|syntax| syntax.escapes);
+        let interpolation = syntax.and_then(|syntax| syntax.interpolation);
+        let mut builder = FragmentBuilder::new();
+        let mut kept = body_start;
+        let mut i = body_start;
+
+        while i < source.len() {
+            let rest = &source[i..];
+            let skipped_until = if rest.starts_with(close) {
+                builder.push(kept, &source[kept..i]);
+                return (builder.finish(), i + close.len());
+            } else if escapes && rest.starts_with('\\') {
+                // The escaped character may be multibyte
+                Some(i + 1 + rest[1..].chars().next().map_or(0, char::len_utf8))
+            } else if let Some((open, close)) =
+                interpolation.filter(|(open, _)| rest.starts_with(open))
+            {
+                Some(
+                    rest[open.len()..]
+                        .find(close)
+                        .map_or(source.len(), |end| i + open.len() + end + close.len()),
+                )
+            } else {
+                None
+            };
+
+            match skipped_until {
+                Some(end) => {
+                    builder.push(kept, &source[kept..i]);
+                    builder.push_gap(i, end);
+                    i = end;
+                    kept = i;
+                }
+                None => i += rest.chars().next().map_or(1, char::len_utf8),
+            }
+        }
+
+        builder.push(kept, &source[kept..]);
+        (builder.finish(), source.len())
+    }
+}
+
+impl Extractor for SourceCodeExtractor {
+    fn extract(&self, source: &str) -> Vec<Fragment> {
+        let mut fragments = Vec::new();
+        let mut i = 0;
+
+        while i < source.len() {
+            if let Some((body_start, body_end, end)) = self.block_comment(source, i) {
+                fragments.push(Fragment::verbatim(source, body_start, body_end));
+                i = end;
+            } else if let Some((body_start, end)) = self.line_comment(source, i) {
+                fragments.push(Fragment::verbatim(source, body_start, end));
+                i = end;
+            } else if let Some(end) = self.char_literal(source, i) {
+                i = end;
+            } else if let Some((open_len, close)) = self.raw_string(source, i) {
+                let (fragment, end) = self.string_literal(source, i + open_len, &close, None);
+                fragments.extend(fragment);
+                i = end;
+            } else if let Some(syntax) = self
+                .syntax
+                .strings
+                .iter()
+                .find(|syntax| source[i..].starts_with(syntax.open))
+            {
+                let (fragment, end) =
+                    self.string_literal(source, i + syntax.open.len(), syntax.close, Some(syntax));
+                fragments.extend(fragment);
+                i = end;
+            } else {
+                i += source[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+
+        fragments
+    }
+}
+
+fn is_identifier_byte(bytes: &[u8], i: usize) -> bool {
+    bytes
+        .get(i)
+        .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::fragment::extracted_words;
+
+    // Words with their line and column, checking each maps back to itself in the source
+    fn words(language: Language, source: &str) -> Vec<(String, usize, usize)> {
+        extracted_words(&SourceCodeExtractor::new(language), source)
+            .into_iter()
+            .map(|(word, line, column, source_text)| {
+                assert_eq!(word, source_text);
+                (word, line, column)
+            })
+            .collect()
+    }
+
+    fn word(text: &str, line: usize, column: usize) -> (String, usize, usize) {
+        (text.to_string(), line, column)
+    }
+
+    #[test]
+    fn extracts_rust() {
+        let source = "/// Doc cmment\n\
+                      fn f<'a>(x: &'a str) { let s = \"strng\\twth\"; // trailng\n\
+                      let q = '\"'; let r = r#\"raw \"qtd\" txt\"#; b'x';\n\
+                      /* outr /* nestd */ stil */ let n = 1; }";
+        assert_eq!(
+            words(Language::Rust, source),
+            [
+                word("Doc", 1, 5),
+                word("cmment", 1, 9),
+                word("strng", 2, 33),
+                word("wth", 2, 40),
+                word("trailng", 2, 49),
+                word("raw", 3, 25),
+                word("qtd", 3, 30),
+                word("txt", 3, 35),
+                word("outr", 4, 4),
+                word("nestd", 4, 12),
+                word("stil", 4, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_python() {
+        let source = "# commnt\nx = 'sngle' + \"dble\"\n\"\"\"Docstrng\nsecnd \"line\"\n\"\"\"";
+        assert_eq!(
+            words(Language::Python, source),
+            [
+                word("commnt", 1, 3),
+                word("sngle", 2, 6),
+                word("dble", 2, 16),
+                word("Docstrng", 3, 4),
+                word("secnd", 4, 1),
+                word("line", 4, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_javascript() {
+        let source = "const s = `tmpl ${cde + \"x\"} txt`; /* blck\ncmnt */ f('qute');";
+        assert_eq!(
+            words(Language::JavaScript, source),
+            [
+                word("tmpl", 1, 12),
+                word("txt", 1, 30),
+                word("blck", 1, 39),
+                word("cmnt", 2, 1),
+                word("qute", 2, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_go_and_c() {
+        assert_eq!(
+            words(Language::Go, "s := `raw\\nstr` // cmnt"),
+            [word("raw", 1, 7), word("nstr", 1, 11), word("cmnt", 1, 20)]
+        );
+        assert_eq!(
+            words(Language::C, "/* a /* b */ c = 'x'; puts(\"hllo\");"),
+            [
+                word("a", 1, 4),
+                word("b", 1, 9),
+                word("x", 1, 19),
+                word("hllo", 1, 29),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_shell() {
+        let source = "echo $# ${#arr} \"hi $(cmd arg) thre\" 'lit $x' # cmnt\nx=1;# lst";
+        assert_eq!(
+            words(Language::Shell, source),
+            [
+                word("hi", 1, 18),
+                word("thre", 1, 32),
+                word("lit", 1, 39),
+                word("x", 1, 44),
+                word("cmnt", 1, 49),
+                word("lst", 2, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_unterminated_literals_to_the_end() {
+        assert_eq!(
+            words(Language::C, "x = \"opn strng\n/* unclsd"),
+            [
+                word("opn", 1, 6),
+                word("strng", 1, 10),
+                word("unclsd", 2, 4)
+            ]
+        );
+    }
+}
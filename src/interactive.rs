@@ -0,0 +1,217 @@
+use crate::spell_check::autofix::{preserve_case, Correction};
+use crate::spell_check::misspelling::Misspelling;
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::unicode_normalization::nfc;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+  1, 2 replace with that suggestion      r  replace with a word you type
+  R    replace every occurrence          i  ignore      I  ignore every occurrence
+  a    add to the personal dictionary    x  save the file and exit
+  q    quit without saving the file      ?  show this help";
+
+/// What the session ended a file with.
+pub enum Review {
+    /// Replacements to write back to the file
+    Corrections(Vec<Correction>),
+    /// The user quit, nothing is written
+    Quit,
+}
+
+/// An aspell-like correction session, walking through misspellings one at a time.
+///
+/// Choices made with "replace all", "ignore all" and "add" carry over to the next files
+/// of the session; added words are also kept for the caller to save.
+pub struct Session<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    // Reverse video around the word, for terminals; brackets otherwise
+    color: bool,
+    ignored: HashSet<String>,
+    replace_all: HashMap<String, String>,
+    added_words: Vec<String>,
+    exited: bool,
+}
+
+impl<R: BufRead, W: Write> Session<R, W> {
+    pub fn new(input: R, output: W, color: bool) -> Self {
+        Session {
+            input,
+            output,
+            color,
+            ignored: HashSet::new(),
+            replace_all: HashMap::new(),
+            added_words: Vec::new(),
+            exited: false,
+        }
+    }
+
+    /// Whether the user asked to stop after the current file.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Words added to the personal dictionary since the last call.
+    pub fn take_added_words(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.added_words)
+    }
+
+    /// Asks what to do with each of `misspellings`, found in `source` and ordered by position.
+    pub fn review(
+        &mut self,
+        file_path: &str,
+        source: &str,
+        misspellings: &[Misspelling],
+        checker: &dyn SpellChecker,
+    ) -> io::Result<Review> {
+        let mut corrections = Vec::new();
+        for misspelling in misspellings {
+            let key = nfc(&misspelling.word).to_lowercase();
+            if self.ignored.contains(&key) {
+                continue;
+            }
+            if let Some(replacement) = self.replace_all.get(&key) {
+                corrections.push(replace(
+                    misspelling,
+                    preserve_case(&misspelling.word, replacement),
+                ));
+                continue;
+            }
+
+            let suggestions = checker.suggest_correction(&key);
+            self.show(file_path, source, misspelling, &suggestions)?;
+            loop {
+                let Some(answer) = self.prompt("? ")? else {
+                    // End of input saves what was decided so far
+                    self.exited = true;
+                    return Ok(Review::Corrections(corrections));
+                };
+
+                match answer.as_str() {
+                    "i" => {}
+                    "I" => {
+                        self.ignored.insert(key.clone());
+                    }
+                    "a" => {
+                        self.ignored.insert(key.clone());
+                        self.added_words.push(key.clone());
+                    }
+                    "r" => match self.prompt("Replace with: ")? {
+                        Some(replacement) if !replacement.is_empty() => {
+                            corrections.push(replace(misspelling, replacement));
+                        }
+                        _ => continue,
+                    },
+                    "R" => match self.prompt("Replace every occurrence with (number or word): ")? {
+                        Some(choice) if !choice.is_empty() => {
+                            let replacement = pick(&choice, &suggestions).unwrap_or(choice);
+                            self.replace_all
+                                .insert(key.clone(), replacement.to_lowercase());
+                            corrections.push(replace(
+                                misspelling,
+                                preserve_case(&misspelling.word, &replacement),
+                            ));
+                        }
+                        _ => continue,
+                    },
+                    "x" => {
+                        self.exited = true;
+                        return Ok(Review::Corrections(corrections));
+                    }
+                    "q" => {
+                        self.exited = true;
+                        return Ok(Review::Quit);
+                    }
+                    choice => match pick(choice, &suggestions) {
+                        Some(suggestion) => {
+                            corrections.push(replace(
+                                misspelling,
+                                preserve_case(&misspelling.word, &suggestion),
+                            ));
+                        }
+                        None => {
+                            writeln!(self.output, "{}", HELP)?;
+                            continue;
+                        }
+                    },
+                }
+                break;
+            }
+        }
+        Ok(Review::Corrections(corrections))
+    }
+
+    fn show(
+        &mut self,
+        file_path: &str,
+        source: &str,
+        misspelling: &Misspelling,
+        suggestions: &[String],
+    ) -> io::Result<()> {
+        let range = &misspelling.range;
+        let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[range.end..]
+            .find('\n')
+            .map_or(source.len(), |i| range.end + i);
+        let (open, close) = if self.color {
+            ("\x1b[7m", "\x1b[0m")
+        } else {
+            ("[", "]")
+        };
+
+        writeln!(self.output)?;
+        writeln!(
+            self.output,
+            "{}:{}:{}: {}",
+            file_path, misspelling.line, misspelling.column, misspelling.word
+        )?;
+        writeln!(
+            self.output,
+            "  {}{}{}{}{}",
+            source[line_start..range.start].trim_start(),
+            open,
+            &source[range.clone()],
+            close,
+            source[range.end..line_end].trim_end()
+        )?;
+        for (i, suggestion) in suggestions.iter().enumerate() {
+            writeln!(
+                self.output,
+                "  {}) {}",
+                i + 1,
+                preserve_case(&misspelling.word, suggestion)
+            )?;
+        }
+        if suggestions.is_empty() {
+            writeln!(self.output, "  (no suggestions)")?;
+        }
+        Ok(())
+    }
+
+    // None at the end of the input
+    fn prompt(&mut self, message: &str) -> io::Result<Option<String>> {
+        write!(self.output, "{}", message)?;
+        self.output.flush()?;
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(answer.trim().to_string()))
+    }
+}
+
+// The suggestion numbered `choice` on screen
+fn pick(choice: &str, suggestions: &[String]) -> Option<String> {
+    let number = choice.parse::<usize>().ok()?;
+    suggestions.get(number.checked_sub(1)?).cloned()
+}
+
+fn replace(misspelling: &Misspelling, replacement: String) -> Correction {
+    Correction {
+        range: misspelling.range.clone(),
+        word: misspelling.word.clone(),
+        replacement,
+        confidence: 1.0,
+    }
+}
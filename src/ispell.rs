@@ -0,0 +1,206 @@
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+/// Answers the `ispell -a` pipe protocol spoken by Emacs flyspell and editor plugins.
+///
+/// Each input line gets one response line per word, `*` when it is correct unless
+/// terse, `& word count offset: suggestions` or `# word offset` when it is not,
+/// followed by an empty line. Lines starting with `*`, `&`, `@` or `#` manage the
+/// dictionary instead. Offsets are 0-based character offsets in the line as sent, so a
+/// leading `^` counts: ispell 3.1 and aspell answer `^hello wrold` with offset 7, and
+/// flyspell takes the one back off with `ispell-offset`.
+pub struct IspellPipe<'a> {
+    tokenizer: &'a dyn Tokenizer,
+    filter: &'a TokenFilter,
+    checker: &'a dyn SpellChecker,
+    // Accepted until the end of the session, from `@word`
+    session_words: HashSet<String>,
+    // Added with `*word` or `&word`, saved to the personal dictionary on `#`
+    personal_words: Vec<String>,
+    terse: bool,
+}
+
+impl<'a> IspellPipe<'a> {
+    pub fn new(
+        tokenizer: &'a dyn Tokenizer,
+        filter: &'a TokenFilter,
+        checker: &'a dyn SpellChecker,
+    ) -> Self {
+        IspellPipe {
+            tokenizer,
+            filter,
+            checker,
+            session_words: HashSet::new(),
+            personal_words: Vec::new(),
+            terse: false,
+        }
+    }
+
+    /// Answers every line of `input` until it ends; `save` gets the words to add to the
+    /// personal dictionary whenever the client sends `#`.
+    pub fn run(
+        &mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+        mut save: impl FnMut(&[String]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        writeln!(
+            output,
+            "@(#) International Ispell Version 3.1.20 (but really rust-spell-checker {})",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            match line.chars().next() {
+                Some('*') => self.add_word(&line[1..]),
+                Some('&') => self.add_word(&line[1..].to_lowercase()),
+                Some('@') => {
+                    self.session_words
+                        .insert(nfc(line[1..].trim()).to_lowercase());
+                }
+                Some('#') => {
+                    save(&self.personal_words)?;
+                    self.personal_words.clear();
+                }
+                Some('!') => self.terse = true,
+                Some('%') => self.terse = false,
+                // Mode switches and `$$` commands we have no use for
+                Some('~' | '+' | '-' | '$') => {}
+                Some('^') => self.check_line(&line[1..], 1, &mut output)?,
+                _ => self.check_line(line, 0, &mut output)?,
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn add_word(&mut self, word: &str) {
+        let word = nfc(word.trim()).into_owned();
+        if word.is_empty() {
+            return;
+        }
+        self.session_words.insert(word.to_lowercase());
+        self.personal_words.push(word);
+    }
+
+    // `shift` is the length of the prefix stripped from the line, which offsets include
+    fn check_line(&self, line: &str, shift: usize, output: &mut impl Write) -> io::Result<()> {
+        let tokens = self.tokenizer.tokens(line);
+        for token in self.filter.filter(line, tokens) {
+            let word = nfc(token.text).to_lowercase();
+            let offset = shift + token.column - 1;
+            if self.session_words.contains(&word) || self.checker.check_word(&word) {
+                if !self.terse {
+                    writeln!(output, "*")?;
+                }
+                continue;
+            }
+
+            let suggestions = self.checker.suggest_correction(&word);
+            if suggestions.is_empty() {
+                writeln!(output, "# {} {}", token.text, offset)?;
+            } else {
+                writeln!(
+                    output,
+                    "& {} {} {}: {}",
+                    token.text,
+                    suggestions.len(),
+                    offset,
+                    suggestions.join(", ")
+                )?;
+            }
+        }
+        writeln!(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::levenshtein_checker::LevenshteinChecker;
+    use crate::utils::token_filter::TokenFilterOptions;
+    use crate::utils::tokenizer::tokenizer_by_name;
+
+    // Runs a pipe over `input` and returns its answer lines and the words it saved
+    fn run(input: &str) -> (Vec<String>, Vec<Vec<String>>) {
+        let tokenizer = tokenizer_by_name("regex").unwrap();
+        let filter = TokenFilter::new(TokenFilterOptions::default());
+        let dictionary = ["naïve", "world", "hello", "café"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let checker = LevenshteinChecker::new(dictionary);
+        let mut pipe = IspellPipe::new(&*tokenizer, &filter, &checker);
+
+        let mut output = Vec::new();
+        let mut saved = Vec::new();
+        pipe.run(input.as_bytes(), &mut output, |words| {
+            saved.push(words.to_vec());
+            Ok(())
+        })
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        (output.lines().map(str::to_string).collect(), saved)
+    }
+
+    #[test]
+    fn scripted_session() {
+        let input = "^hello wrold\n\
+                     hello wrold\n\
+                     ^*zzyzx wrold\n\
+                     ^café wrold\n\
+                     ^zzyzx\n\
+                     @zzyzx\n\
+                     ^zzyzx\n\
+                     *Qwghlm\n\
+                     &Xyzzy\n\
+                     ^qwghlm xyzzy\n\
+                     #\n\
+                     !\n\
+                     ^hello wrold\n\
+                     %\n\
+                     ^hello\r\n";
+        let (lines, saved) = run(input);
+        assert!(lines[0].starts_with("@(#) International Ispell Version 3.1.20"));
+        assert_eq!(
+            &lines[1..],
+            [
+                // Offsets count the caret, as in the line sent
+                "*",
+                "& wrold 1 7: world",
+                "",
+                "*",
+                "& wrold 1 6: world",
+                "",
+                // The rest of a `^` line is text even when it starts with a command
+                "# zzyzx 2",
+                "& wrold 1 8: world",
+                "",
+                // Offsets are in characters, not bytes
+                "*",
+                "& wrold 1 6: world",
+                "",
+                "# zzyzx 1",
+                "",
+                "*",
+                "",
+                "*",
+                "*",
+                "",
+                // Terse mode only reports misspellings
+                "& wrold 1 7: world",
+                "",
+                "*",
+                "",
+            ]
+        );
+        assert_eq!(saved, [["Qwghlm", "xyzzy"]]);
+    }
+}
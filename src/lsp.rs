@@ -0,0 +1,542 @@
+use crate::config::Config;
+use crate::frontend::fragment::tokenize_fragments;
+use crate::spell_check::autofix::preserve_case;
+use crate::spell_check::misspelling::{check_tokens, Misspelling};
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use log::{debug, info};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+const SOURCE: &str = "spellcheck";
+const ADD_TO_DICTIONARY: &str = "spellcheck.addToDictionary";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Language server publishing spelling diagnostics over stdio.
+///
+/// The checker is loaded once and shared by every document. Documents are synced
+/// incrementally and rechecked whole on each change; suggestions are only computed
+/// when the editor asks for code actions. Positions are in UTF-16 code units, as
+/// LSP requires by default.
+pub struct LanguageServer<'a> {
+    config: &'a Config,
+    tokenizer: &'a dyn Tokenizer,
+    filter: &'a TokenFilter,
+    checker: &'a dyn SpellChecker,
+    // Open documents by URI
+    documents: HashMap<String, String>,
+    // Added to the dictionary during this session
+    accepted: HashSet<String>,
+    shutdown: bool,
+}
+
+impl<'a> LanguageServer<'a> {
+    pub fn new(
+        config: &'a Config,
+        tokenizer: &'a dyn Tokenizer,
+        filter: &'a TokenFilter,
+        checker: &'a dyn SpellChecker,
+    ) -> Self {
+        LanguageServer {
+            config,
+            tokenizer,
+            filter,
+            checker,
+            documents: HashMap::new(),
+            accepted: HashSet::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Serves messages until `exit` or the end of `input`; `save` gets each word added
+    /// to the dictionary. Returns whether the client shut the server down properly.
+    pub fn run(
+        &mut self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+        mut save: impl FnMut(&[String]) -> io::Result<()>,
+    ) -> io::Result<bool> {
+        while let Some(content) = read_message(&mut input)? {
+            let message: Value = match serde_json::from_slice(&content) {
+                Ok(message) => message,
+                Err(e) => {
+                    // Its id is unknown, so the error answers no request in particular
+                    let response = json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": PARSE_ERROR, "message": e.to_string() }
+                    });
+                    write_message(&mut output, &response)?;
+                    continue;
+                }
+            };
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+            debug!("LSP {}", method);
+
+            if method == "exit" {
+                break;
+            }
+            let result = match method {
+                "initialize" => Ok(Some(capabilities())),
+                "shutdown" => {
+                    self.shutdown = true;
+                    Ok(Some(Value::Null))
+                }
+                "textDocument/didOpen" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri, &mut output)?;
+                    Ok(None)
+                }
+                "textDocument/didChange" => self.change_document(params, &mut output)?,
+                "textDocument/didClose" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    self.documents.remove(uri);
+                    let params = json!({ "uri": uri, "diagnostics": [] });
+                    write_notification(&mut output, "textDocument/publishDiagnostics", params)?;
+                    Ok(None)
+                }
+                "textDocument/codeAction" => self.code_actions(params).map(Some),
+                "workspace/executeCommand" => {
+                    self.execute_command(params, &mut output, &mut save)?
+                }
+                _ if message.get("id").is_some() => {
+                    Err((METHOD_NOT_FOUND, format!("Unsupported method {}", method)))
+                }
+                // Other notifications need no answer
+                _ => Ok(None),
+            };
+
+            let Some(id) = message.get("id") else {
+                continue;
+            };
+            let response = match result {
+                Ok(result) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": result.unwrap_or(Value::Null)
+                }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message }
+                }),
+            };
+            write_message(&mut output, &response)?;
+        }
+        info!("LSP session ended");
+        Ok(self.shutdown)
+    }
+
+    fn change_document(
+        &mut self,
+        params: &Value,
+        output: &mut impl Write,
+    ) -> io::Result<Result<Option<Value>, (i64, String)>> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(text) = self.documents.get_mut(uri) else {
+            return Ok(Err((INVALID_PARAMS, format!("{} is not open", uri))));
+        };
+
+        let changes = params["contentChanges"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for change in changes {
+            let new_text = change["text"].as_str().unwrap_or_default();
+            match change.get("range") {
+                Some(range) => {
+                    let start = offset_at(text, &range["start"]);
+                    let end = offset_at(text, &range["end"]).max(start);
+                    text.replace_range(start..end, new_text);
+                }
+                None => *text = new_text.to_string(),
+            }
+        }
+        self.publish_diagnostics(uri, output)?;
+        Ok(Ok(None))
+    }
+
+    fn code_actions(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(text) = self.documents.get(uri) else {
+            return Err((INVALID_PARAMS, format!("{} is not open", uri)));
+        };
+
+        let mut actions = Vec::new();
+        let diagnostics = params["context"]["diagnostics"].as_array();
+        for diagnostic in diagnostics.into_iter().flatten() {
+            if diagnostic["source"] != SOURCE {
+                continue;
+            }
+            let range = &diagnostic["range"];
+            let start = offset_at(text, &range["start"]);
+            let end = offset_at(text, &range["end"]).max(start);
+            let word = &text[start..end];
+
+            for suggestion in self.checker.suggest_correction(&nfc(word).to_lowercase()) {
+                let replacement = preserve_case(word, &suggestion);
+                actions.push(json!({
+                    "title": format!("Replace with \"{}\"", replacement),
+                    "kind": "quickfix",
+                    "diagnostics": [diagnostic],
+                    "edit": {
+                        "changes": { uri: [{ "range": range, "newText": replacement }] }
+                    }
+                }));
+            }
+            actions.push(json!({
+                "title": format!("Add \"{}\" to the dictionary", word),
+                "kind": "quickfix",
+                "diagnostics": [diagnostic],
+                "command": {
+                    "title": "Add to dictionary",
+                    "command": ADD_TO_DICTIONARY,
+                    "arguments": [word]
+                }
+            }));
+        }
+        Ok(Value::Array(actions))
+    }
+
+    fn execute_command(
+        &mut self,
+        params: &Value,
+        output: &mut impl Write,
+        save: &mut impl FnMut(&[String]) -> io::Result<()>,
+    ) -> io::Result<Result<Option<Value>, (i64, String)>> {
+        if params["command"] != ADD_TO_DICTIONARY {
+            return Ok(Err((
+                INVALID_PARAMS,
+                format!("Unknown command {}", params["command"]),
+            )));
+        }
+        let Some(word) = params["arguments"][0].as_str() else {
+            return Ok(Err((INVALID_PARAMS, "Missing word".to_string())));
+        };
+
+        let word = nfc(word).to_lowercase();
+        save(std::slice::from_ref(&word))?;
+        self.accepted.insert(word);
+
+        // The word may be flagged in every open document
+        let uris = self.documents.keys().cloned().collect::<Vec<_>>();
+        for uri in uris {
+            self.publish_diagnostics(&uri, output)?;
+        }
+        Ok(Ok(None))
+    }
+
+    fn publish_diagnostics(&self, uri: &str, output: &mut impl Write) -> io::Result<()> {
+        let text = &self.documents[uri];
+        let diagnostics = self
+            .misspellings(uri, text)
+            .iter()
+            .map(|misspelling| {
+                json!({
+                    "range": {
+                        "start": position_at(text, misspelling.range.start),
+                        "end": position_at(text, misspelling.range.end)
+                    },
+                    // Information, spelling is rarely an error
+                    "severity": 3,
+                    "source": SOURCE,
+                    "message": format!("Unknown word \"{}\"", misspelling.word)
+                })
+            })
+            .collect::<Vec<_>>();
+        let params = json!({ "uri": uri, "diagnostics": diagnostics });
+        write_notification(output, "textDocument/publishDiagnostics", params)
+    }
+
+    fn misspellings(&self, uri: &str, text: &str) -> Vec<Misspelling> {
+        let frontend = self.config.frontend_for(&uri_to_path(uri));
+        let fragments = self.config.extractor(&frontend).unwrap().extract(text);
+        let tokens = tokenize_fragments(text, &fragments, self.tokenizer, self.filter);
+        let mut result = check_tokens(&tokens, self.checker);
+        result.misspellings.retain(|misspelling| {
+            !self
+                .accepted
+                .contains(&nfc(&misspelling.word).to_lowercase())
+        });
+        result.misspellings
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": {
+                "openClose": true,
+                // Incremental
+                "change": 2
+            },
+            "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+            "executeCommandProvider": { "commands": [ADD_TO_DICTIONARY] }
+        },
+        "serverInfo": {
+            "name": "rust-spell-checker",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    })
+}
+
+// Content of the next message, None at the end of the input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    Ok(Some(content))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+fn write_notification(output: &mut impl Write, method: &str, params: Value) -> io::Result<()> {
+    let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+    write_message(output, &message)
+}
+
+// LSP position of the byte `offset` of `text`
+fn position_at(text: &str, offset: usize) -> Value {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = text[..line_start].matches('\n').count();
+    let character = text[line_start..offset].encode_utf16().count();
+    json!({ "line": line, "character": character })
+}
+
+// Byte offset of an LSP position in `text`, clamped to the end of its line
+fn offset_at(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+
+    let line_start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    if line_start >= text.len() {
+        return text.len();
+    }
+
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+// Only the extension matters, to pick the front-end
+fn uri_to_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next().unwrap_or(b'0'), bytes.next().unwrap_or(b'0')];
+            let hex = std::str::from_utf8(&hex).unwrap_or("00");
+            decoded.push(u8::from_str_radix(hex, 16).unwrap_or(b'?'));
+        } else {
+            decoded.push(b);
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::levenshtein_checker::LevenshteinChecker;
+    use crate::utils::token_filter::TokenFilterOptions;
+    use crate::utils::tokenizer::tokenizer_by_name;
+
+    const URI: &str = "file:///tmp/notes.txt";
+
+    fn frame(message: &Value) -> String {
+        let content = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> String {
+        frame(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+    }
+
+    fn notification(method: &str, params: Value) -> String {
+        frame(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    // Runs a server over `input` and returns whether it was shut down and what it wrote
+    fn run(input: &str) -> (bool, Vec<Value>) {
+        let config = Config::default().with_defaults();
+        let tokenizer = tokenizer_by_name("regex").unwrap();
+        let filter = TokenFilter::new(TokenFilterOptions::default());
+        let dictionary = ["naïve", "world", "hello"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let checker = LevenshteinChecker::new(dictionary);
+        let mut server = LanguageServer::new(&config, &*tokenizer, &filter, &checker);
+
+        let mut output = Vec::new();
+        let shutdown = server
+            .run(input.as_bytes(), &mut output, |_| Ok(()))
+            .unwrap();
+        let mut output = output.as_slice();
+        let mut messages = Vec::new();
+        while let Some(content) = read_message(&mut output).unwrap() {
+            messages.push(serde_json::from_slice(&content).unwrap());
+        }
+        (shutdown, messages)
+    }
+
+    fn ranges(diagnostics: &Value) -> Vec<(u64, u64, u64)> {
+        diagnostics["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| {
+                let range = &diagnostic["range"];
+                assert_eq!(range["start"]["line"], range["end"]["line"]);
+                (
+                    range["start"]["line"].as_u64().unwrap(),
+                    range["start"]["character"].as_u64().unwrap(),
+                    range["end"]["character"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scripted_session() {
+        // "😀" and the unknown letter "𝒳" are two UTF-16 code units each, "ï" one
+        let text = "naïve 😀 wrold\nhello 𝒳 wrold\n";
+        let diagnostic = json!({
+            "range": {
+                "start": { "line": 0, "character": 8 },
+                "end": { "line": 0, "character": 13 }
+            },
+            "source": SOURCE,
+            "message": "Unknown word \"wrold\""
+        });
+        let input = [
+            request(1, "initialize", json!({ "capabilities": {} })),
+            notification("initialized", json!({})),
+            notification(
+                "textDocument/didOpen",
+                json!({
+                    "textDocument": { "uri": URI, "languageId": "plaintext", "version": 1, "text": text }
+                }),
+            ),
+            // "naïve" becomes "naïv"
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": URI, "version": 2 },
+                    "contentChanges": [{
+                        "range": {
+                            "start": { "line": 0, "character": 4 },
+                            "end": { "line": 0, "character": 5 }
+                        },
+                        "text": ""
+                    }]
+                }),
+            ),
+            request(
+                2,
+                "textDocument/codeAction",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "range": diagnostic["range"],
+                    "context": { "diagnostics": [diagnostic] }
+                }),
+            ),
+            request(3, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ]
+        .concat();
+
+        let (shutdown, messages) = run(&input);
+        assert!(shutdown);
+        assert_eq!(messages.len(), 5);
+
+        assert_eq!(messages[0]["id"], 1);
+        assert_eq!(
+            messages[0]["result"]["capabilities"]["textDocumentSync"]["change"],
+            2
+        );
+
+        assert_eq!(messages[1]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(messages[1]["params"]["uri"], URI);
+        assert_eq!(ranges(&messages[1]), [(0, 9, 14), (1, 6, 8), (1, 9, 14)]);
+
+        assert_eq!(
+            ranges(&messages[2]),
+            [(0, 0, 4), (0, 8, 13), (1, 6, 8), (1, 9, 14)]
+        );
+
+        assert_eq!(messages[3]["id"], 2);
+        let actions = messages[3]["result"].as_array().unwrap();
+        assert_eq!(actions[0]["title"], "Replace with \"world\"");
+        let edit = &actions[0]["edit"]["changes"][URI][0];
+        assert_eq!(edit["range"], diagnostic["range"]);
+        assert_eq!(edit["newText"], "world");
+        let last = actions.last().unwrap();
+        assert_eq!(last["command"]["arguments"][0], "wrold");
+
+        assert_eq!(
+            messages[4],
+            json!({ "jsonrpc": "2.0", "id": 3, "result": null })
+        );
+    }
+
+    #[test]
+    fn malformed_message() {
+        let input = [
+            "Content-Length: 5\r\n\r\n{bad,".to_string(),
+            request(1, "shutdown", Value::Null),
+        ]
+        .concat();
+
+        let (shutdown, messages) = run(&input);
+        assert!(shutdown);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["id"], Value::Null);
+        assert_eq!(messages[0]["error"]["code"], PARSE_ERROR);
+        assert_eq!(messages[1]["id"], 1);
+    }
+}
@@ -1,31 +1,78 @@
+use crate::cli::{
+    AccentMode, Algorithm, AuditArgs, BuildIndexArgs, CheckerArgs, Cli, Command, ConfigArgs,
+    ConfigCommandArgs, DaemonArgs, DictCommand, DictionaryArgs, EditorArgs, ServeArgs, SimilarArgs,
+    SuggestArgs,
+};
+use crate::commands::bench::bench;
+use crate::commands::check::{check_files, file_findings};
+use crate::commands::diff::check_diff;
+use crate::commands::fix::{correct_interactively, fix_files};
+use crate::config::{CheckerConfig, Config, ConfigCache};
+use crate::daemon::{Daemon, DaemonClient, DaemonCommand, Response};
+use crate::ispell::IspellPipe;
+use crate::lsp::LanguageServer;
+use crate::server::CheckService;
+use crate::spell_check::accent_folding::{AccentFoldingChecker, AccentFoldingIndex};
+use crate::spell_check::contractions::ContractionAwareChecker;
 use crate::spell_check::precomputed_levenshtein_checker::PrecomputedLevenshteinChecker;
-use crate::spell_check::spell_checker::SpellChecker;
+use crate::spell_check::spell_checker::{SpellChecker, SuggestionLimits};
 use crate::spell_check::wagner_fischer::WagnerFischerChecker;
-use cudarc::driver::{CudaDevice, DriverError};
-use cudarc::nvrtc::Ptx;
-use cudarc::*;
-use log::{debug, info};
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use clap::Parser;
+use log::{debug, error, info};
 use std::collections::HashSet;
-use std::error::Error;
-use std::ffi::CString;
+use std::path::Path;
+use std::process::ExitCode;
 use std::time::Instant;
 
-extern crate rayon;
-
-use rayon::prelude::*;
+mod cli;
+mod config;
+mod daemon;
+mod interactive;
+mod ispell;
+mod lsp;
+mod server;
 
 mod utils {
+    pub mod audit_dictionary;
+    pub mod baseline;
+    pub mod diff;
+    pub mod identifier_tokenizer;
     pub mod io;
     pub mod load_dictionary;
     pub mod read_dataset;
+    pub mod token_filter;
     pub mod tokenizer;
+    pub mod unicode_normalization;
 }
 
 mod cuda {}
 
+mod commands {
+    pub mod bench;
+    pub mod check;
+    pub mod diff;
+    pub mod fix;
+}
+
+mod frontend {
+    pub mod fragment;
+    pub mod html;
+    pub mod latex;
+    pub mod markdown;
+    pub mod source_code;
+}
+
 mod spell_check {
+    pub mod accent_folding;
+    pub mod autofix;
+    pub mod contractions;
+    pub mod edit_distance;
     pub mod hash_map_look_up;
     pub mod levenshtein_checker;
+    pub mod misspelling;
     pub mod precomputed_levenshtein_checker;
     pub mod soundex_checker;
     pub mod spell_checker;
@@ -33,203 +80,560 @@ mod spell_check {
     // pub mod levenshtein_checker_bk_map;
 }
 
-fn main() -> Result<(), DriverError> {
+fn main() -> ExitCode {
     env_logger::init();
 
-    // Initialize the CUDA API with cudarc
-    let dev = CudaDevice::new(0)?;
+    match Cli::parse().command {
+        Command::Check(args) => check_files(&args),
+        Command::Diff(args) => check_diff(&args),
+        Command::Fix(args) => fix_files(&args),
+        Command::Interactive(args) => correct_interactively(&args),
+        Command::Pipe(args) => answer_ispell_pipe(&args),
+        Command::Lsp(args) => serve_language_server(&args),
+        Command::Serve(args) => serve_http(&args),
+        Command::Daemon(args) => run_daemon(&args),
+        Command::Suggest(args) => suggest_word(&args),
+        Command::Bench(args) => match bench(&args) {
+            Ok(code) => code,
+            Err(e) => {
+                error!("CUDA initialization failed: {:?}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Command::BuildIndex(args) => build_neighbor_index(&args),
+        Command::Dict(DictCommand::Audit(args)) => audit_dictionary(&args),
+        Command::Dict(DictCommand::Similar(args)) => print_similar_words(&args),
+        Command::Config(args) => print_config(&args),
+    }
+}
 
-    dev.load_ptx(
-        Ptx::from_file("src/cuda/suggest_corrections_kernel.ptx"),
-        "cuda",
-        &["suggest_corrections_kernel."],
+// Builds the tokenizer, filter and layered checker `config` describes and runs `f` with them
+fn with_configured_checker<R>(
+    config: &Config,
+    index_file_path: &str,
+    f: impl FnOnce(&dyn Tokenizer, &TokenFilter, &dyn SpellChecker) -> R,
+) -> std::io::Result<R> {
+    let ignored_words = config.load_ignored_words()?;
+    let dictionary = load_dictionaries(&config.dictionary_files())?;
+    let checker = build_checker(
+        config.checker.algorithm(),
+        &dictionary,
+        index_file_path,
+        config.checker.limits(),
     )?;
+    let accent_index = load_accent_index(&config.checker, &dictionary);
+    let tokenizer = utils::tokenizer::tokenizer_by_name(config.checker.tokenizer()).unwrap();
+    let filter = TokenFilter::new(config.filter.options())
+        .with_ignored_words(ignored_words)
+        .with_ignore_patterns(&config.ignore_patterns)
+        .unwrap();
 
-    let function = dev.get_func("cuda", "suggest_corrections_kernel").unwrap();
+    Ok(with_checker_layers(
+        &*checker,
+        &config.checker,
+        accent_index.as_ref(),
+        |checker| f(&*tokenizer, &filter, checker),
+    ))
+}
+
+fn suggest_word(args: &SuggestArgs) -> ExitCode {
+    let config = match current_dir_config(
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let word = nfc(&args.word).to_lowercase();
+    if let Some((correct, suggestions)) = suggest_with_daemon(args, &config, &word) {
+        if correct {
+            info!("{} is in the dictionary", args.word);
+        }
+        for suggestion in suggestions {
+            println!("{}", suggestion);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let checker = load_dictionaries(&config.dictionary_files()).and_then(|dictionary| {
+        let checker = build_checker(
+            config.checker.algorithm(),
+            &dictionary,
+            &args.dictionary.index,
+            config.checker.limits(),
+        )?;
+        Ok((dictionary, checker))
+    });
+    let (dictionary, checker) = match checker {
+        Ok(checker) => checker,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let accent_index = load_accent_index(&config.checker, &dictionary);
+
+    with_checker_layers(
+        &*checker,
+        &config.checker,
+        accent_index.as_ref(),
+        |checker| {
+            if checker.check_word(&word) {
+                info!("{} is in the dictionary", args.word);
+            }
+            for suggestion in checker.suggest_correction(&word) {
+                println!("{}", suggestion);
+            }
+        },
+    );
+    ExitCode::SUCCESS
+}
 
-    let normal_dictionary_file_path = "data/dictionary/dict.txt";
-    let dataset_file_path = "data/dataset/book.txt";
-    let dictionary_file_path = "data/dictionary/insane-dict.txt";
+// None when no daemon runs with the same configuration
+fn suggest_with_daemon(
+    args: &SuggestArgs,
+    config: &Config,
+    word: &str,
+) -> Option<(bool, Vec<String>)> {
+    if args.daemon.no_daemon {
+        return None;
+    }
+    let mut daemon = DaemonClient::connect(&args.daemon.socket)?;
+    let command = DaemonCommand::Suggest {
+        word: word.to_string(),
+    };
+    match daemon.request(&config.fingerprint(&args.dictionary.index), command) {
+        Ok(Response::Suggestions {
+            correct,
+            suggestions,
+        }) => Some((correct, suggestions)),
+        Ok(response) => {
+            debug!("The daemon answered {:?}", response);
+            None
+        }
+        Err(e) => {
+            error!("The daemon failed, suggesting in this process: {}", e);
+            None
+        }
+    }
+}
 
-    let (dictionary, dataset) = load_data(dictionary_file_path, dataset_file_path);
-    let (dataset_words, dictionary_words) = tokenize_data(&dataset, &dictionary);
+fn answer_ispell_pipe(args: &EditorArgs) -> ExitCode {
+    let config = match current_dir_config(
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let personal = config.personal_dictionary.clone().unwrap();
 
-    let hashmap_lookup = spell_check::hash_map_look_up::HashMapLookup::new(dictionary.clone());
-    let levenshtein_checker = spell_check::levenshtein_checker::LevenshteinChecker::new(
-        dictionary.clone().into_iter().collect(),
+    let answered = with_configured_checker(
+        &config,
+        &args.dictionary.index,
+        |tokenizer, filter, checker| {
+            IspellPipe::new(tokenizer, filter, checker).run(
+                std::io::stdin().lock(),
+                std::io::stdout().lock(),
+                |words| utils::load_dictionary::append_words(&personal, words),
+            )
+        },
     );
-    let wagner_fischer_checker = WagnerFischerChecker::new(dictionary.clone());
-
-    let checkers: Vec<(&dyn SpellChecker, &str)> = vec![
-        (&hashmap_lookup, "hashmap"),
-        (&levenshtein_checker, "levenshtein"),
-        (&wagner_fischer_checker, "wagner_fischer"),
-    ];
-
-    for (checker, name) in checkers {
-        let (unknown_words, duration_look_up) = check_unknown_words(&dataset_words, checker);
-        print_unknown_words_info(
-            &unknown_words,
-            &dictionary_words,
-            &dataset_words,
-            duration_look_up,
-            name,
-        );
-
-        let unknown_words_set = filter_unknown_words(&unknown_words);
-        let chunk_size = unknown_words_set.len() / rayon::current_num_threads();
-        let (corrections, duration_correction) =
-            suggest_corrections(&unknown_words_set, checker, chunk_size);
-
-        print_correction_info(&unknown_words_set, duration_correction, &corrections, name);
+    match answered.and_then(|answered| answered) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::from(2)
+        }
     }
-    Ok(())
 }
 
-fn load_data(dictionary_file_path: &str, dataset_file_path: &str) -> (HashSet<String>, String) {
-    let dictionary = utils::load_dictionary::load_dictionary(dictionary_file_path).unwrap();
-    let dataset = utils::read_dataset::read_dataset(dataset_file_path).unwrap();
-    (dictionary, dataset)
+fn serve_language_server(args: &EditorArgs) -> ExitCode {
+    let config = match current_dir_config(
+        &args.config,
+        args.algorithm,
+        &args.dictionary,
+        &args.checker,
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let personal = config.personal_dictionary.clone().unwrap();
+
+    // Loaded once, then reused for every document and request
+    let served = with_configured_checker(
+        &config,
+        &args.dictionary.index,
+        |tokenizer, filter, checker| {
+            LanguageServer::new(&config, tokenizer, filter, checker).run(
+                std::io::stdin().lock(),
+                std::io::stdout().lock(),
+                |words| utils::load_dictionary::append_words(&personal, words),
+            )
+        },
+    );
+    match served.and_then(|served| served) {
+        Ok(true) => ExitCode::SUCCESS,
+        // The protocol asks for a failure when `exit` comes without `shutdown`
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::from(2)
+        }
+    }
 }
 
-fn tokenize_data<'a>(
-    dataset: &str,
-    dictionary: &'a HashSet<String>,
-) -> (Vec<String>, Vec<&'a String>) {
-    let dataset_words = utils::tokenizer::tokenizer(dataset);
-    let dictionary_words = dictionary.par_iter().collect::<Vec<&'a String>>();
-    (dataset_words, dictionary_words)
+fn serve_http(args: &ServeArgs) -> ExitCode {
+    let editor = &args.editor;
+    let mut config = match current_dir_config(
+        &editor.config,
+        editor.algorithm,
+        &editor.dictionary,
+        &editor.checker,
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    // The service keeps personal words apart from the checker, so they can be deleted
+    let personal = config.personal_dictionary.take().unwrap();
+
+    let listener = match std::net::TcpListener::bind(&args.listen) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("{}: {}", args.listen, e);
+            return ExitCode::from(2);
+        }
+    };
+    let served = with_configured_checker(
+        &config,
+        &editor.dictionary.index,
+        |tokenizer, filter, checker| {
+            CheckService::new(&config, tokenizer, filter, checker, &personal)?
+                .with_max_body_size(args.max_body_size)
+                .serve(listener)
+        },
+    );
+    match served.and_then(|served| served) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::from(2)
+        }
+    }
 }
 
-fn check_unknown_words(
-    dataset_words: &Vec<String>,
-    checker: &dyn SpellChecker,
-) -> (HashSet<String>, std::time::Duration) {
-    let start = Instant::now();
-    let unknown_words = dataset_words
-        .par_iter()
-        .filter(|word| !checker.check_word(word.as_str()))
-        .cloned()
-        .collect::<HashSet<String>>();
-    let duration = start.elapsed();
-    (unknown_words, duration)
+fn run_daemon(args: &DaemonArgs) -> ExitCode {
+    if args.stop {
+        let stopped = DaemonClient::connect(&args.socket)
+            .map(|mut daemon| daemon.request("", DaemonCommand::Stop));
+        return match stopped {
+            Some(Ok(Response::Stopping)) => ExitCode::SUCCESS,
+            Some(Ok(response)) => {
+                error!("The daemon answered {:?}", response);
+                ExitCode::from(2)
+            }
+            Some(Err(e)) => {
+                error!("{}: {}", args.socket, e);
+                ExitCode::from(2)
+            }
+            None => {
+                error!("No daemon listens on {}", args.socket);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let editor = &args.editor;
+    let config = match current_dir_config(
+        &editor.config,
+        editor.algorithm,
+        &editor.dictionary,
+        &editor.checker,
+    ) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let daemon = Daemon::new(&args.socket, config.fingerprint(&editor.dictionary.index));
+    let listener = match daemon.bind() {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("{}: {}", args.socket, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let served = with_configured_checker(
+        &config,
+        &editor.dictionary.index,
+        |tokenizer, filter, checker| {
+            daemon.serve(listener, |command| match command {
+                DaemonCommand::Check { file, frontend } => {
+                    if config.extractor(&frontend).is_none() {
+                        return Response::Error {
+                            message: format!("unknown front-end {}", frontend),
+                        };
+                    }
+                    let mut findings = Vec::new();
+                    let checked = file_findings(
+                        &file,
+                        &frontend,
+                        &config,
+                        tokenizer,
+                        filter,
+                        checker,
+                        |_, _, chunk_findings| {
+                            findings.extend(chunk_findings);
+                            Ok(())
+                        },
+                    );
+                    match checked {
+                        Ok(()) => Response::Findings { findings },
+                        Err(e) => Response::Error {
+                            message: e.to_string(),
+                        },
+                    }
+                }
+                DaemonCommand::Suggest { word } => Response::Suggestions {
+                    correct: checker.check_word(&word),
+                    suggestions: checker.suggest_correction(&word),
+                },
+                DaemonCommand::Stop => Response::Stopping,
+            })
+        },
+    );
+    match served.and_then(|served| served) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Configuration for commands that check no particular file
+fn current_dir_config(
+    config_args: &ConfigArgs,
+    algorithm: Option<Algorithm>,
+    dictionary: &DictionaryArgs,
+    checker: &CheckerArgs,
+) -> std::io::Result<Config> {
+    let mut config = ConfigCache::new(config_args)?
+        .config_for(Path::new("."))?
+        .clone();
+    config.apply_args(algorithm, dictionary, checker);
+    Ok(config.with_defaults())
+}
+
+fn print_config(args: &ConfigCommandArgs) -> ExitCode {
+    let config = ConfigCache::new(&args.config)
+        .and_then(|mut configs| configs.config_for(Path::new(&args.path)).cloned());
+    match config {
+        Ok(config) => {
+            print!("{}", toml::to_string(&config.with_defaults()).unwrap());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("{}: {}", args.path, e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn build_checker(
+    algorithm: Algorithm,
+    dictionary: &HashSet<String>,
+    index_file_path: &str,
+    limits: SuggestionLimits,
+) -> std::io::Result<Box<dyn SpellChecker>> {
+    Ok(match algorithm {
+        Algorithm::Hashmap => Box::new(spell_check::hash_map_look_up::HashMapLookup::new(
+            dictionary.clone(),
+        )),
+        Algorithm::Levenshtein => Box::new(
+            spell_check::levenshtein_checker::LevenshteinChecker::new(dictionary.clone())
+                .with_limits(limits),
+        ),
+        Algorithm::WagnerFischer => {
+            Box::new(WagnerFischerChecker::new(dictionary.clone()).with_limits(limits))
+        }
+        Algorithm::NeighborGraph => {
+            let graph = match load_neighbor_graph(index_file_path)? {
+                Some(graph) => graph,
+                // Expanding single edits by the graph reaches one edit further than it links
+                None => {
+                    let distance = limits.max_distance.saturating_sub(1).max(1);
+                    info!(
+                        "No neighbor graph at {}, building one in memory (distance <= {}); \
+                         save it with `build-index`",
+                        index_file_path, distance
+                    );
+                    PrecomputedLevenshteinChecker::with_distance(
+                        dictionary.iter().cloned().collect(),
+                        distance,
+                    )
+                }
+            };
+            Box::new(graph.with_limits(limits))
+        }
+    })
 }
 
-fn filter_unknown_words(unknown_words: &HashSet<String>) -> HashSet<&String> {
-    unknown_words
-        .iter()
-        .filter(|word| !word.chars().any(|c| c.is_digit(10)))
-        .collect::<HashSet<&String>>()
+fn load_accent_index(
+    checker: &CheckerConfig,
+    dictionary: &HashSet<String>,
+) -> Option<AccentFoldingIndex> {
+    checker.accents.map(|_| AccentFoldingIndex::new(dictionary))
 }
 
-fn suggest_corrections(
-    unknown_words_set: &HashSet<&String>,
+// Runs `f` with `checker` wrapped in the accent-folding and contraction layers
+fn with_checker_layers<R>(
     checker: &dyn SpellChecker,
-    chunk_size: usize,
-) -> (Vec<Vec<String>>, std::time::Duration) {
+    config: &CheckerConfig,
+    accent_index: Option<&AccentFoldingIndex>,
+    f: impl FnOnce(&dyn SpellChecker) -> R,
+) -> R {
+    let accept_unaccented = config.accents == Some(AccentMode::Accept);
+    let accent_checker =
+        accent_index.map(|index| AccentFoldingChecker::new(checker, index, accept_unaccented));
+    let checker: &dyn SpellChecker = match &accent_checker {
+        Some(accent_checker) => accent_checker,
+        None => checker,
+    };
+    f(&ContractionAwareChecker::new(checker))
+}
+
+fn load_dictionaries(dictionary_file_paths: &[String]) -> std::io::Result<HashSet<String>> {
+    let mut dictionary = HashSet::new();
+    for file_path in dictionary_file_paths {
+        let words = utils::load_dictionary::load_dictionary(file_path)
+            .map_err(|e| with_file_path(file_path, e))?;
+        dictionary.extend(words);
+    }
+    Ok(dictionary)
+}
+
+// `e` naming the file it happened on, for errors logged far from where they occur
+fn with_file_path(file_path: &str, e: std::io::Error) -> std::io::Error {
+    std::io::Error::new(e.kind(), format!("{}: {}", file_path, e))
+}
+
+fn build_neighbor_index(args: &BuildIndexArgs) -> ExitCode {
+    let dictionary = match utils::load_dictionary::load_dictionary(&args.dictionary) {
+        Ok(dictionary) => dictionary,
+        Err(e) => {
+            error!("{}: {}", args.dictionary, e);
+            return ExitCode::from(2);
+        }
+    };
+
     let start = Instant::now();
-    let unknown_words_vec: Vec<_> = unknown_words_set.clone().into_iter().collect();
-    let corrections: Vec<_> = unknown_words_vec
-        .par_chunks(chunk_size)
-        .map(|chunk| {
-            chunk
-                .par_iter()
-                .map(|word| checker.suggest_correction(word.as_str()))
-                .collect::<Vec<_>>()
-        })
-        .flatten()
-        .collect();
-    let duration = start.elapsed();
-    (corrections, duration)
-}
-
-fn print_unknown_words_info(
-    unknown_words: &HashSet<String>,
-    dictionary_words: &Vec<&String>,
-    dataset_words: &Vec<String>,
-    duration: std::time::Duration,
-    name: &str,
-) {
-    info!("__________________________________________________________________________");
-    info!("Unknown words {}: {:?}", name, unknown_words.len());
-    info!("Dictionary words: {}", dictionary_words.len());
-    info!("Dataset words: {}", dataset_words.len());
+    let edges = match PrecomputedLevenshteinChecker::build_to_file(
+        dictionary.into_iter().collect(),
+        args.distance,
+        &args.output,
+    ) {
+        Ok(edges) => edges,
+        Err(e) => {
+            error!("{}: {}", args.output, e);
+            return ExitCode::from(2);
+        }
+    };
+
     info!(
-        "Time elapsed in checking unknown words using {}: {:?}",
-        name, duration
+        "Neighbor graph with {} edges (distance <= {}) written to {} in {:?}",
+        edges,
+        args.distance,
+        args.output,
+        start.elapsed()
     );
-    info!("__________________________________________________________________________");
+    ExitCode::SUCCESS
+}
+
+fn audit_dictionary(args: &AuditArgs) -> ExitCode {
+    let frequencies = match &args.frequencies {
+        Some(file_path) => match utils::audit_dictionary::load_frequencies(file_path) {
+            Ok(frequencies) => Some(frequencies),
+            Err(e) => {
+                error!("{}: {}", file_path, e);
+                return ExitCode::from(2);
+            }
+        },
+        None => {
+            info!("No frequency list given, skipping near-duplicate detection");
+            None
+        }
+    };
+
+    let findings = match utils::audit_dictionary::audit_dictionary(
+        &args.dictionary,
+        frequencies.as_ref(),
+        args.ratio,
+    ) {
+        Ok(findings) => findings,
+        Err(e) => {
+            error!("{}: {}", args.dictionary, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    info!("{} audit findings in {}", findings.len(), args.dictionary);
+    ExitCode::SUCCESS
 }
 
-fn print_correction_info(
-    unknown_words_set: &HashSet<&String>,
-    duration: std::time::Duration,
-    corrections: &Vec<Vec<String>>,
-    name: &str,
-) {
-    info!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-    info!("set of unknown words: {:?}", unknown_words_set.len());
+fn print_similar_words(args: &SimilarArgs) -> ExitCode {
+    let word = args.word.to_lowercase();
+    let graph = match PrecomputedLevenshteinChecker::load(&args.index) {
+        Ok(graph) => graph,
+        Err(e) => {
+            error!("{}: {}", args.index, e);
+            return ExitCode::from(2);
+        }
+    };
+    for (similar, dist) in graph.similar_words(&word) {
+        println!("{}\t{}", similar, dist);
+    }
+    ExitCode::SUCCESS
+}
+
+// The neighbor graph is optional; build it once with `build-index`
+fn load_neighbor_graph(
+    index_file_path: &str,
+) -> std::io::Result<Option<PrecomputedLevenshteinChecker>> {
+    if !std::path::Path::new(index_file_path).exists() {
+        return Ok(None);
+    }
+
+    let start = Instant::now();
+    let graph = PrecomputedLevenshteinChecker::load(index_file_path)
+        .map_err(|e| with_file_path(index_file_path, e))?;
     info!(
-        "Time elapsed in checking unknown words using {} correction: {:?}",
-        name, duration
+        "Loaded neighbor graph with {} edges (distance <= {}) in {:?}",
+        graph.edge_count(),
+        graph.max_distance(),
+        start.elapsed()
     );
-
-    let non_empty_corrections: Vec<_> = corrections.iter().filter(|c| !c.is_empty()).collect();
-
-    info!("{} corrections: {:?}", name, non_empty_corrections.len());
-    debug!("{} corrections: {:?}", name, non_empty_corrections);
-    info!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-}
-
-// let duration_precomputed_levenshtein = Instant::now(); // Start timer
-//
-// let dictionary_vec: Vec<_> = dictionary.into_iter().collect();
-// let precomputed_levenshtein_checker = PrecomputedLevenshteinChecker::new(dictionary_vec.clone());
-//
-// let unknown_words_vec: Vec<_> = unknown_words_set.into_iter().collect();
-//
-// let fix_precomputed_levenshtein_corrections: Vec<_> = unknown_words_vec.par_chunks(chunk_size)
-//     .map(|chunk| {
-//         chunk.iter()
-//             .map(|word| precomputed_levenshtein_checker.suggest_correction(word.as_str()))
-//             .collect::<Vec<_>>()
-//     })
-//     .flatten()
-//     .collect();
-//
-// let duration_precomputed_levenshtein_completed = duration_precomputed_levenshtein.elapsed(); // End timer
-//
-// println!("Time elapsed in checking unknown words using precomputed levenshtein: {:?}", duration_precomputed_levenshtein_completed);
-// println!("Precomputed Levenshtein corrections: {:?}", fix_precomputed_levenshtein_corrections.len());
-// println!("Precomputed Levenshtein corrections: {:?}", fix_precomputed_levenshtein_corrections);
-
-// fn suggest_corrections_cuda(unknown_words_set: &HashSet<&String>, checker: &dyn SpellChecker, chunk_size: usize) -> (Vec<Vec<String>>, std::time::Duration) {
-//     let start = Instant::now();
-//
-//     // Convert the unknown_words_set to a Vec and allocate memory on the GPU
-//     let unknown_words_vec: Vec<_> = unknown_words_set.clone().into_iter().collect();
-//     let mut device_unknown_words = DeviceBuffer::from_slice(&unknown_words_vec).unwrap();
-//
-//     // Allocate memory on the GPU for the corrections
-//     let mut device_corrections = DeviceBuffer::zeros(unknown_words_vec.len()).unwrap();
-//
-//     // Define the grid and block size for the CUDA kernel
-//     let grid_size = (unknown_words_vec.len() + 255) / 256;
-//     let block_size = 256;
-//
-//     // Launch the CUDA kernel
-//     unsafe {
-//         suggest_corrections_kernel<<<grid_size, block_size>>>(
-//             device_unknown_words.as_device_ptr(),
-//             device_corrections.as_device_ptr(),
-//             unknown_words_vec.len()
-//         );
-//     }
-//
-//     // Copy the corrections from the GPU to the CPU
-//     let mut corrections = vec![0; unknown_words_vec.len()];
-//     device_corrections.copy_to(&mut corrections).unwrap();
-//
-//     let duration = start.elapsed();
-//     (corrections, duration)
-// }
+    Ok(Some(graph))
+}
@@ -0,0 +1,587 @@
+use crate::config::Config;
+use crate::frontend::fragment::tokenize_fragments;
+use crate::spell_check::autofix::preserve_case;
+use crate::spell_check::misspelling::{check_tokens, Misspelling};
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::Tokenizer;
+use crate::utils::unicode_normalization::nfc;
+use log::{debug, error, info};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Largest request body accepted by default, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1 << 20;
+
+const MAX_HEADER_SIZE: usize = 16 << 10;
+// Slow or idle clients must not hold their connection thread forever
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// Rejected requests are read this far after the answer, since closing a connection with
+// unread input resets it and the client may never see why it was rejected
+const DRAIN_SIZE: u64 = 1 << 20;
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+// Characters shown on each side of a match in its LanguageTool `context`
+const CONTEXT_CHARS: usize = 40;
+
+// Status code and message of a failed request
+type HttpError = (u16, String);
+
+struct Request {
+    method: String,
+    path: String,
+    // Query string and form body parameters together
+    params: HashMap<String, String>,
+}
+
+/// HTTP service checking text with a checker loaded once and shared by every request.
+///
+/// `/check` (also `/v2/check`) answers in LanguageTool's JSON shape, so LanguageTool
+/// clients can point at it; `/suggest?word=` returns the suggestions for one word, and
+/// `/v2/words` lists, adds and deletes personal dictionary words like LanguageTool's
+/// word API. Parameters come as a query string or a form-encoded body, and every
+/// response closes the connection.
+pub struct CheckService<'a> {
+    config: &'a Config,
+    tokenizer: &'a dyn Tokenizer,
+    filter: &'a TokenFilter,
+    checker: &'a dyn SpellChecker,
+    max_body_size: usize,
+    personal_dictionary: String,
+    // Accepted on top of the checker, lowercased; loaded from and saved to the
+    // personal dictionary
+    personal_words: RwLock<BTreeSet<String>>,
+}
+
+impl<'a> CheckService<'a> {
+    /// `checker` must not include the personal dictionary, since its words can be
+    /// deleted while serving.
+    pub fn new(
+        config: &'a Config,
+        tokenizer: &'a dyn Tokenizer,
+        filter: &'a TokenFilter,
+        checker: &'a dyn SpellChecker,
+        personal_dictionary: &str,
+    ) -> io::Result<Self> {
+        let personal_words = match fs::read_to_string(personal_dictionary) {
+            Ok(contents) => contents
+                .lines()
+                .map(|word| nfc(word.trim()).to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(CheckService {
+            config,
+            tokenizer,
+            filter,
+            checker,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            personal_dictionary: personal_dictionary.to_string(),
+            personal_words: RwLock::new(personal_words),
+        })
+    }
+
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Answers connections until the listener fails, each on its own thread.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        info!("Listening on http://{}", listener.local_addr()?);
+        // Connections block on reads, so they get threads of their own and leave the
+        // rayon pool to the checkers
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        scope.spawn(move || self.answer(stream));
+                    }
+                    Err(e) => error!("Connection failed: {}", e),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn answer(&self, mut stream: TcpStream) {
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let (status, body, rejected) = match self.read_request(&stream) {
+            Ok(request) => {
+                debug!("{} {}", request.method, request.path);
+                match self.route(&request) {
+                    Ok(body) => (200, body, false),
+                    Err(e) => (e.0, error_body(e), false),
+                }
+            }
+            Err(e) => (e.0, error_body(e), true),
+        };
+        if let Err(e) = write_response(&mut stream, status, &body) {
+            debug!("Writing the response failed: {}", e);
+            return;
+        }
+        if rejected {
+            let _ = stream.shutdown(Shutdown::Write);
+            let _ = stream.set_read_timeout(Some(DRAIN_TIMEOUT));
+            let _ = io::copy(&mut (&stream).take(DRAIN_SIZE), &mut io::sink());
+        }
+    }
+
+    fn route(&self, request: &Request) -> Result<Value, HttpError> {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET" | "POST", "/check" | "/v2/check") => self.check(request),
+            ("GET", "/suggest") => self.suggest(request),
+            ("GET", "/v2/words") => {
+                let words = self.personal_words.read().unwrap();
+                Ok(json!({ "words": *words }))
+            }
+            ("POST", "/v2/words/add") => {
+                let word = nfc(required(request, "word")?).to_lowercase();
+                let added = self.personal_words.write().unwrap().insert(word);
+                self.save_personal_words()?;
+                Ok(json!({ "added": added }))
+            }
+            ("POST", "/v2/words/delete") => {
+                let word = nfc(required(request, "word")?).to_lowercase();
+                let deleted = self.personal_words.write().unwrap().remove(&word);
+                self.save_personal_words()?;
+                Ok(json!({ "deleted": deleted }))
+            }
+            (
+                _,
+                "/check" | "/v2/check" | "/suggest" | "/v2/words" | "/v2/words/add"
+                | "/v2/words/delete",
+            ) => Err((405, format!("{} not allowed", request.method))),
+            _ => Err((404, format!("No endpoint at {}", request.path))),
+        }
+    }
+
+    fn check(&self, request: &Request) -> Result<Value, HttpError> {
+        let text = required(request, "text")?;
+        // Not part of LanguageTool's API: the text may also be markup or source code
+        let frontend = request
+            .params
+            .get("frontend")
+            .map_or("plain", String::as_str);
+        let extractor = self
+            .config
+            .extractor(frontend)
+            .ok_or_else(|| (400, format!("Unknown frontend {}", frontend)))?;
+
+        let fragments = extractor.extract(text);
+        let tokens = tokenize_fragments(text, &fragments, self.tokenizer, self.filter);
+        let misspellings = {
+            let personal_words = self.personal_words.read().unwrap();
+            let mut result = check_tokens(&tokens, self.checker);
+            result.misspellings.retain(|misspelling| {
+                !personal_words.contains(&nfc(&misspelling.word).to_lowercase())
+            });
+            result.misspellings
+        };
+
+        let matches = misspellings
+            .iter()
+            .map(|misspelling| self.language_tool_match(text, misspelling))
+            .collect::<Vec<_>>();
+        let language = request
+            .params
+            .get("language")
+            .map_or("auto", String::as_str);
+        Ok(json!({
+            "software": {
+                "name": "rust-spell-checker",
+                "version": env!("CARGO_PKG_VERSION"),
+                "apiVersion": 1
+            },
+            "language": { "name": language, "code": language },
+            "matches": matches
+        }))
+    }
+
+    // Offsets and lengths count UTF-16 code units, like LanguageTool's Java strings
+    fn language_tool_match(&self, text: &str, misspelling: &Misspelling) -> Value {
+        let range = &misspelling.range;
+        let replacements = self
+            .checker
+            .suggest_correction(&nfc(&misspelling.word).to_lowercase())
+            .into_iter()
+            .map(|suggestion| json!({ "value": preserve_case(&misspelling.word, &suggestion) }))
+            .collect::<Vec<_>>();
+
+        let context_start = text[..range.start]
+            .char_indices()
+            .rev()
+            .nth(CONTEXT_CHARS - 1)
+            .map_or(0, |(i, _)| i);
+        let context_end = text[range.end..]
+            .char_indices()
+            .nth(CONTEXT_CHARS)
+            .map_or(text.len(), |(i, _)| range.end + i);
+        let line_start = text[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[range.end..]
+            .find('\n')
+            .map_or(text.len(), |i| range.end + i);
+
+        json!({
+            "message": "Possible spelling mistake found.",
+            "shortMessage": "Spelling mistake",
+            "replacements": replacements,
+            "offset": utf16_len(&text[..range.start]),
+            "length": utf16_len(&misspelling.word),
+            "context": {
+                "text": text[context_start..context_end].replace('\n', " "),
+                "offset": utf16_len(&text[context_start..range.start]),
+                "length": utf16_len(&misspelling.word)
+            },
+            "sentence": text[line_start..line_end].trim(),
+            "type": { "typeName": "UnknownWord" },
+            "rule": {
+                "id": "MORFOLOGIK_RULE",
+                "description": "Possible spelling mistake",
+                "issueType": "misspelling",
+                "category": { "id": "TYPOS", "name": "Possible Typo" }
+            },
+            "ignoreForIncompleteSentence": false,
+            "contextForSureMatch": 0
+        })
+    }
+
+    fn suggest(&self, request: &Request) -> Result<Value, HttpError> {
+        let word = required(request, "word")?;
+        let normalized = nfc(word).to_lowercase();
+        let correct = self.personal_words.read().unwrap().contains(&normalized)
+            || self.checker.check_word(&normalized);
+        let suggestions = self
+            .checker
+            .suggest_correction(&normalized)
+            .into_iter()
+            .map(|suggestion| preserve_case(word, &suggestion))
+            .collect::<Vec<_>>();
+        Ok(json!({ "word": word, "correct": correct, "suggestions": suggestions }))
+    }
+
+    fn save_personal_words(&self) -> Result<(), HttpError> {
+        let personal_words = self.personal_words.read().unwrap();
+        let contents = personal_words
+            .iter()
+            .map(|word| format!("{}\n", word))
+            .collect::<String>();
+        fs::write(&self.personal_dictionary, contents).map_err(|e| {
+            error!("{}: {}", self.personal_dictionary, e);
+            (500, "Saving the personal dictionary failed".to_string())
+        })
+    }
+
+    fn read_request(&self, stream: &TcpStream) -> Result<Request, HttpError> {
+        let mut reader = BufReader::new(stream);
+        let bad_request = |e: io::Error| (400, e.to_string());
+
+        let mut header_size = 0;
+        let mut read_line = |reader: &mut BufReader<&TcpStream>| {
+            let mut line = String::new();
+            (&mut *reader)
+                .take((MAX_HEADER_SIZE - header_size) as u64)
+                .read_line(&mut line)
+                .map_err(bad_request)?;
+            header_size += line.len();
+            if !line.ends_with('\n') {
+                return Err((431, "Request headers too large".to_string()));
+            }
+            Ok(line.trim_end().to_string())
+        };
+
+        let request_line = read_line(&mut reader)?;
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err((400, "Malformed request line".to_string()));
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let mut headers = HashMap::new();
+        loop {
+            let line = read_line(&mut reader)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let content_length = match headers.get("content-length") {
+            Some(length) => length
+                .parse::<usize>()
+                .map_err(|_| (400, "Invalid Content-Length".to_string()))?,
+            None => 0,
+        };
+        if content_length > self.max_body_size {
+            return Err((
+                413,
+                format!("Request body over {} bytes", self.max_body_size),
+            ));
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).map_err(bad_request)?;
+        let body = String::from_utf8(body).map_err(|_| (400, "Body is not UTF-8".to_string()))?;
+
+        let mut params = parse_form(query);
+        let content_type = headers.get("content-type").map_or("", String::as_str);
+        if content_type.starts_with("application/json") {
+            let json: Value =
+                serde_json::from_str(&body).map_err(|e| (400, format!("Invalid JSON: {}", e)))?;
+            for (name, value) in json.as_object().into_iter().flatten() {
+                if let Some(value) = value.as_str() {
+                    params.insert(name.clone(), value.to_string());
+                }
+            }
+        } else {
+            params.extend(parse_form(&body));
+        }
+
+        Ok(Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            params,
+        })
+    }
+}
+
+fn required<'r>(request: &'r Request, name: &str) -> Result<&'r str, HttpError> {
+    request
+        .params
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| (400, format!("Missing parameter {}", name)))
+}
+
+fn error_body((_, message): HttpError) -> Value {
+    json!({ "error": message })
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// `application/x-www-form-urlencoded` pairs, also used by query strings
+fn parse_form(form: &str) -> HashMap<String, String> {
+    form.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = [bytes.next().unwrap_or(b'0'), bytes.next().unwrap_or(b'0')];
+                let hex = std::str::from_utf8(&hex).unwrap_or("00");
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or(b'?'));
+            }
+            b => decoded.push(b),
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::levenshtein_checker::LevenshteinChecker;
+    use crate::utils::token_filter::TokenFilterOptions;
+    use crate::utils::tokenizer::tokenizer_by_name;
+    use std::thread;
+
+    // Leaked so that `serve` can run on a thread outliving the test
+    fn service() -> &'static CheckService<'static> {
+        let config = Box::leak(Box::new(Config::default().with_defaults()));
+        let tokenizer = Box::leak(tokenizer_by_name("regex").unwrap());
+        let filter = Box::leak(Box::new(TokenFilter::new(TokenFilterOptions::default())));
+        let dictionary = ["hello", "world"].into_iter().map(str::to_string).collect();
+        let checker = Box::leak(Box::new(LevenshteinChecker::new(dictionary)));
+        let personal = std::env::temp_dir().join(format!("server-words-{}", std::process::id()));
+        let service = CheckService::new(
+            config,
+            tokenizer,
+            filter,
+            checker,
+            personal.to_str().unwrap(),
+        )
+        .unwrap()
+        .with_max_body_size(64);
+        Box::leak(Box::new(service))
+    }
+
+    // Sends `request` and returns the status and JSON body of the answer
+    fn send(address: std::net::SocketAddr, request: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        // Well under `READ_TIMEOUT`, so a stalled server fails the test
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    // Answers `request` with `CheckService::answer` over a loopback connection
+    fn answer(request: &str) -> (u16, Value) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = {
+            let request = request.to_string();
+            thread::spawn(move || send(address, &request))
+        };
+        let (stream, _) = listener.accept().unwrap();
+        service().answer(stream);
+        client.join().unwrap()
+    }
+
+    fn post(path: &str, content_type: &str, body: &str) -> String {
+        format!(
+            "POST {} HTTP/1.1\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            content_type,
+            body.len(),
+            body
+        )
+    }
+
+    fn offsets(response: &Value) -> Vec<(u64, u64)> {
+        response["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| (m["offset"].as_u64().unwrap(), m["length"].as_u64().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_query_form_and_json_parameters() {
+        let (status, response) = answer("GET /check?text=hello+wrold HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(offsets(&response), [(6, 5)]);
+        assert_eq!(response["matches"][0]["replacements"][0]["value"], "world");
+
+        let form = post(
+            "/v2/check",
+            "application/x-www-form-urlencoded",
+            "language=en-US&text=wrold%20hello",
+        );
+        let (status, response) = answer(&form);
+        assert_eq!(status, 200);
+        assert_eq!(response["language"]["code"], "en-US");
+        assert_eq!(offsets(&response), [(0, 5)]);
+
+        let json = post("/check", "application/json", r#"{"text": "hello wrold"}"#);
+        assert_eq!(offsets(&answer(&json).1), [(6, 5)]);
+
+        let (status, response) = answer("GET /suggest?word=Wrold HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(response["suggestions"], json!(["World"]));
+    }
+
+    #[test]
+    fn counts_offsets_in_utf16_code_units() {
+        // The emoji is two code units
+        let json = post(
+            "/check",
+            "application/json",
+            r#"{"text": "😀 hello wrold"}"#,
+        );
+        let (_, response) = answer(&json);
+        assert_eq!(offsets(&response), [(9, 5)]);
+        assert_eq!(response["matches"][0]["context"]["offset"], 9);
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let cases = [
+            ("GET /nowhere HTTP/1.1\r\n\r\n".to_string(), 404),
+            ("DELETE /check HTTP/1.1\r\n\r\n".to_string(), 405),
+            ("GET /check HTTP/1.1\r\n\r\n".to_string(), 400),
+            ("garbage\r\n\r\n".to_string(), 400),
+            (
+                "POST /check HTTP/1.1\r\nContent-Length: many\r\n\r\n".to_string(),
+                400,
+            ),
+            (post("/check", "application/json", "{"), 400),
+            // Refused from its Content-Length, before the body is read
+            (
+                post("/check", "text/plain", &format!("text={}", "a".repeat(60))),
+                413,
+            ),
+            (
+                format!(
+                    "GET /check HTTP/1.1\r\nX: {}\r\n\r\n",
+                    "x".repeat(MAX_HEADER_SIZE)
+                ),
+                431,
+            ),
+        ];
+        for (request, expected) in cases {
+            let (status, response) = answer(&request);
+            assert_eq!(status, expected, "{}", request);
+            assert!(response["error"].is_string());
+        }
+        // A body of exactly the limit is accepted
+        let body = format!("text={}", "a".repeat(59));
+        assert_eq!(answer(&post("/check", "text/plain", &body)).0, 200);
+    }
+
+    #[test]
+    fn idle_connections_do_not_stall_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let service = service();
+        thread::spawn(move || service.serve(listener));
+
+        // More idle clients than the rayon pool has workers
+        let idle = (0..rayon::current_num_threads() + 1)
+            .map(|_| TcpStream::connect(address).unwrap())
+            .collect::<Vec<_>>();
+        let (status, response) = send(address, "GET /check?text=wrold HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 200);
+        assert_eq!(offsets(&response), [(0, 5)]);
+        for stream in idle {
+            stream.shutdown(Shutdown::Both).unwrap();
+        }
+    }
+}
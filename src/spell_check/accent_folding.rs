@@ -0,0 +1,148 @@
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::unicode_normalization::fold_accents;
+use std::collections::{HashMap, HashSet};
+
+/// Dictionary words grouped by their accent-folded form, so "resume" finds "résumé".
+pub struct AccentFoldingIndex {
+    folded: HashMap<String, Vec<String>>,
+}
+
+impl AccentFoldingIndex {
+    pub fn new(dictionary: &HashSet<String>) -> Self {
+        let mut folded: HashMap<String, Vec<String>> = HashMap::new();
+        for word in dictionary {
+            let key = fold_accents(word);
+            if key != word.as_str() {
+                folded
+                    .entry(key.into_owned())
+                    .or_default()
+                    .push(word.clone());
+            }
+        }
+        for words in folded.values_mut() {
+            words.sort();
+        }
+        AccentFoldingIndex { folded }
+    }
+
+    /// Accented dictionary words that fold to the same letters as `word`.
+    pub fn accented_forms(&self, word: &str) -> &[String] {
+        self.folded
+            .get(fold_accents(word).as_ref())
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Wraps a checker so that words typed without their accents are either accepted
+/// or, when `accept_unaccented` is off, corrected to the accented dictionary form.
+pub struct AccentFoldingChecker<'a> {
+    inner: &'a dyn SpellChecker,
+    index: &'a AccentFoldingIndex,
+    accept_unaccented: bool,
+}
+
+impl<'a> AccentFoldingChecker<'a> {
+    pub fn new(
+        inner: &'a dyn SpellChecker,
+        index: &'a AccentFoldingIndex,
+        accept_unaccented: bool,
+    ) -> Self {
+        AccentFoldingChecker {
+            inner,
+            index,
+            accept_unaccented,
+        }
+    }
+}
+
+impl SpellChecker for AccentFoldingChecker<'_> {
+    fn check_word(&self, word: &str) -> bool {
+        if self.inner.check_word(word) {
+            return true;
+        }
+        // Only words without any accents are accepted, a wrong accent is still an error
+        self.accept_unaccented
+            && fold_accents(word) == word
+            && !self.index.accented_forms(word).is_empty()
+    }
+
+    fn suggest_correction(&self, word: &str) -> Vec<String> {
+        let mut suggestions: Vec<String> = self
+            .index
+            .accented_forms(word)
+            .iter()
+            .filter(|accented| accented.as_str() != word)
+            .cloned()
+            .collect();
+        for suggestion in self.inner.suggest_correction(word) {
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::hash_map_look_up::HashMapLookup;
+
+    fn dictionary() -> HashSet<String> {
+        ["résumé", "resume", "café", "naïve", "über", "cafe\u{301}s"]
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn folds_accents() {
+        let cases = [
+            ("résumé", "resume"),
+            // Decomposed input folds like composed input
+            ("re\u{301}sume\u{301}", "resume"),
+            ("Ångström", "Angstrom"),
+            ("naïve", "naive"),
+            ("привет", "привет"),
+            ("йод", "иод"),
+            ("λόγος", "λογος"),
+            ("ø", "ø"),
+            ("plain", "plain"),
+        ];
+        for (word, folded) in cases {
+            assert_eq!(fold_accents(word), folded, "{}", word);
+        }
+    }
+
+    #[test]
+    fn finds_accented_forms() {
+        let index = AccentFoldingIndex::new(&dictionary());
+        assert_eq!(index.accented_forms("resume"), ["résumé"]);
+        assert_eq!(index.accented_forms("re\u{301}sume"), ["résumé"]);
+        assert_eq!(index.accented_forms("cafe"), ["café"]);
+        // Dictionary words are folded whether they are composed or not
+        assert_eq!(index.accented_forms("cafes"), ["cafe\u{301}s"]);
+        assert!(index.accented_forms("plain").is_empty());
+    }
+
+    #[test]
+    fn accepts_or_corrects_unaccented_words() {
+        let dictionary = dictionary();
+        let index = AccentFoldingIndex::new(&dictionary);
+        let inner = HashMapLookup::new(dictionary);
+
+        let accepting = AccentFoldingChecker::new(&inner, &index, true);
+        assert!(accepting.check_word("naive"));
+        assert!(accepting.check_word("café"));
+        // A wrong accent is not a missing one
+        assert!(!accepting.check_word("cafè"));
+
+        let correcting = AccentFoldingChecker::new(&inner, &index, false);
+        assert!(!correcting.check_word("naive"));
+        assert_eq!(correcting.suggest_correction("naive")[0], "naïve");
+        assert_eq!(correcting.suggest_correction("cafè")[0], "café");
+        // Both spellings are words, the accented one is suggested first
+        assert!(correcting.check_word("resume"));
+        assert_eq!(correcting.suggest_correction("resume")[0], "résumé");
+    }
+}
@@ -0,0 +1,336 @@
+use crate::spell_check::edit_distance::{graphemes, transposition_distance};
+use crate::spell_check::misspelling::Misspelling;
+use crate::utils::unicode_normalization::nfc;
+use std::ops::Range;
+
+/// Confidence needed before a suggestion is applied without review.
+pub const DEFAULT_CONFIDENCE: f64 = 0.7;
+
+// Unchanged lines shown around each change of a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// A replacement of one misspelled word.
+#[derive(Debug, Clone)]
+pub struct Correction {
+    /// Byte range of the word in the file
+    pub range: Range<usize>,
+    pub word: String,
+    pub replacement: String,
+    pub confidence: f64,
+}
+
+/// Picks the closest of `suggestions` for `misspelling`, cased like the word it replaces.
+pub fn correction(misspelling: &Misspelling, suggestions: &[String]) -> Option<Correction> {
+    // Re-ranked with swapped letters as one edit; the sort is stable, so ties keep
+    // the checker's order
+    let word = nfc(&misspelling.word).to_lowercase();
+    let mut ranked = suggestions.to_vec();
+    ranked.sort_by_cached_key(|suggestion| transposition_distance(&word, suggestion));
+
+    let top = ranked.first()?;
+    Some(Correction {
+        range: misspelling.range.clone(),
+        word: misspelling.word.clone(),
+        replacement: preserve_case(&misspelling.word, top),
+        confidence: confidence(&misspelling.word, &ranked),
+    })
+}
+
+/// How sure we are that the first of `suggestions` is the intended word, from 0 to 1.
+///
+/// A single edit in a long word is more telling than one in a short word, and another
+/// suggestion as close as the first halves the confidence, since we could as well have
+/// picked it. Swapped letters count as one edit, so "recieve" is not confidently fixed
+/// to "relieve" when "receive" is among the suggestions.
+pub fn confidence(word: &str, suggestions: &[String]) -> f64 {
+    let word = nfc(word).to_lowercase();
+    let Some((top, others)) = suggestions.split_first() else {
+        return 0.0;
+    };
+    let distance = transposition_distance(&word, top);
+    let length = graphemes(&word).len().max(1);
+    let closeness = 1.0 - (distance as f64 / length as f64).min(1.0);
+
+    let margin = others
+        .iter()
+        .map(|other| {
+            let gap = transposition_distance(&word, other).saturating_sub(distance);
+            ((gap + 1) as f64 / 2.0).min(1.0)
+        })
+        .fold(1.0, f64::min);
+    closeness * margin
+}
+
+/// Cases `replacement` like `original`: "HELPFULL" gives "HELPFUL", "Helpfull"
+/// "Helpful"; words in mixed case keep the dictionary form.
+pub fn preserve_case(original: &str, replacement: &str) -> String {
+    let letters = original
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<Vec<_>>();
+    let capitals = letters.iter().filter(|c| c.is_uppercase()).count();
+
+    // A single capital is a title-cased word rather than an acronym
+    if capitals > 1 && capitals == letters.len() {
+        return replacement.to_uppercase();
+    }
+    if capitals == 1 && letters[0].is_uppercase() {
+        let mut chars = replacement.chars();
+        if let Some(first) = chars.next() {
+            return first.to_uppercase().chain(chars).collect();
+        }
+    }
+    replacement.to_string()
+}
+
+/// Whether the word at `range` of `source` can be replaced without touching anything
+/// else. Front-ends map some words back across markup, such as `wo<b>rd` or `hy\-phen`,
+/// and replacing their whole range would delete it.
+pub fn is_replaceable(source: &str, range: &Range<usize>, word: &str) -> bool {
+    source
+        .get(range.clone())
+        .is_some_and(|text| text == word && !text.contains('\n'))
+}
+
+/// Replaces every corrected word of `source`; corrections must not overlap. Those that
+/// are not `is_replaceable` are skipped, so no line break is ever added or removed.
+pub fn apply_corrections(source: &str, corrections: &[Correction]) -> String {
+    let mut corrections = corrections
+        .iter()
+        .filter(|correction| is_replaceable(source, &correction.range, &correction.word))
+        .collect::<Vec<_>>();
+    corrections.sort_unstable_by_key(|correction| correction.range.start);
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut end = 0;
+    for correction in corrections {
+        fixed.push_str(&source[end..correction.range.start]);
+        fixed.push_str(&correction.replacement);
+        end = correction.range.end;
+    }
+    fixed.push_str(&source[end..]);
+    fixed
+}
+
+/// Unified diff of a file before and after its corrections, ready for `git apply`.
+///
+/// `apply_corrections` never adds or removes line breaks, so line `i` of one text is
+/// line `i` of the other and no longest-common-subsequence search is needed.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    assert_eq!(
+        old_lines.len(),
+        new_lines.len(),
+        "corrections changed the line breaks of {}",
+        path
+    );
+    let changed = (0..old_lines.len())
+        .filter(|&i| old_lines[i] != new_lines[i])
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Relative paths get the `a/` and `b/` prefixes `git apply` and `patch -p1` strip
+    let (old_path, new_path) = if path.starts_with('/') {
+        (path.to_string(), path.to_string())
+    } else {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        (format!("a/{}", path), format!("b/{}", path))
+    };
+    let mut diff = format!("--- {}\n+++ {}\n", old_path, new_path);
+
+    let mut first = 0;
+    while first < changed.len() {
+        // Changes whose contexts touch share a hunk
+        let mut last = first;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * DIFF_CONTEXT + 1
+        {
+            last += 1;
+        }
+        let start = changed[first].saturating_sub(DIFF_CONTEXT);
+        let end = (changed[last] + DIFF_CONTEXT + 1).min(old_lines.len());
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            end - start,
+            start + 1,
+            end - start
+        ));
+
+        let mut line = start;
+        while line < end {
+            if old_lines[line] == new_lines[line] {
+                push_diff_line(&mut diff, ' ', old_lines[line]);
+                line += 1;
+                continue;
+            }
+            // A run of changed lines is shown removed, then added
+            let run_end = (line..end)
+                .find(|&i| old_lines[i] == new_lines[i])
+                .unwrap_or(end);
+            for old_line in &old_lines[line..run_end] {
+                push_diff_line(&mut diff, '-', old_line);
+            }
+            for new_line in &new_lines[line..run_end] {
+                push_diff_line(&mut diff, '+', new_line);
+            }
+            line = run_end;
+        }
+        first = last + 1;
+    }
+    diff
+}
+
+fn push_diff_line(diff: &mut String, marker: char, line: &str) {
+    diff.push(marker);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn misspelling(source: &str, word: &str) -> Misspelling {
+        let start = source.find(word).unwrap();
+        Misspelling {
+            word: word.to_string(),
+            range: start..start + word.len(),
+            line: 1,
+            column: start + 1,
+        }
+    }
+
+    fn correct(source: &str, word: &str, suggestions: &[&str]) -> Correction {
+        correction(&misspelling(source, word), &strings(suggestions)).unwrap()
+    }
+
+    #[test]
+    fn halves_confidence_on_ties() {
+        let alone = confidence("definately", &strings(&["definitely"]));
+        assert!((alone - 0.9).abs() < 1e-9, "{}", alone);
+        // A runner-up one edit further away does not lower it
+        let further = confidence("definately", &strings(&["definitely", "defiantly"]));
+        assert!((further - 0.9).abs() < 1e-9, "{}", further);
+        let tied = confidence("definately", &strings(&["definitely", "definatel"]));
+        assert!((tied - 0.45).abs() < 1e-9, "{}", tied);
+        assert_eq!(confidence("word", &[]), 0.0);
+        // More edits than letters leaves no confidence at all
+        assert_eq!(confidence("ab", &strings(&["xyz"])), 0.0);
+    }
+
+    #[test]
+    fn ranks_transpositions_as_one_edit() {
+        let source = "I recieve mail";
+        let fixed = correct(source, "recieve", &["relieve", "receive", "recipe"]);
+        assert_eq!(fixed.replacement, "relieve");
+        assert!(fixed.confidence < DEFAULT_CONFIDENCE);
+
+        let fixed = correct(source, "recieve", &["recipe", "receive"]);
+        assert_eq!(fixed.replacement, "receive");
+        assert!(
+            fixed.confidence >= DEFAULT_CONFIDENCE,
+            "{}",
+            fixed.confidence
+        );
+        assert_eq!(fixed.range, 2..9);
+    }
+
+    #[test]
+    fn preserves_case() {
+        let cases = [
+            ("teh", "the", "the"),
+            ("Teh", "the", "The"),
+            ("TEH", "the", "THE"),
+            ("tEH", "the", "the"),
+            ("I", "a", "A"),
+            ("Écoel", "école", "École"),
+            ("HELPFULL", "helpful", "HELPFUL"),
+        ];
+        for (original, replacement, expected) in cases {
+            assert_eq!(
+                preserve_case(original, replacement),
+                expected,
+                "{}",
+                original
+            );
+        }
+        assert_eq!(correct("Teh end", "Teh", &["the"]).replacement, "The");
+    }
+
+    #[test]
+    fn applies_only_verbatim_corrections() {
+        let source = "wo<b>rd tset\nnext";
+        let replacement = |start: usize, end: usize, word: &str, replacement: &str| Correction {
+            range: start..end,
+            word: word.to_string(),
+            replacement: replacement.to_string(),
+            confidence: 1.0,
+        };
+        let corrections = [
+            replacement(8, 12, "tset", "test"),
+            // Spans markup, as front-ends map `wo<b>rd` back to one word
+            replacement(0, 7, "word", "ward"),
+            // A stale range no longer holding the word
+            replacement(13, 17, "nxet", "next"),
+        ];
+        assert_eq!(
+            apply_corrections(source, &corrections),
+            "wo<b>rd test\nnext"
+        );
+        assert!(!is_replaceable("a\nb", &(0..3), "a\nb"));
+        assert!(!is_replaceable("short", &(3..9), "rt"));
+    }
+
+    #[test]
+    fn writes_diffs_git_applies() {
+        let mut lines = (1..=20).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        lines[0] = "frist line".to_string();
+        lines[9] = "middel line".to_string();
+        lines[19] = "lsat line".to_string();
+        let old = lines.join("\n");
+        let corrections = [("frist", "first"), ("middel", "middle"), ("lsat", "last")]
+            .into_iter()
+            .map(|(word, replacement)| Correction {
+                range: misspelling(&old, word).range,
+                word: word.to_string(),
+                replacement: replacement.to_string(),
+                confidence: 1.0,
+            })
+            .collect::<Vec<_>>();
+        let new = apply_corrections(&old, &corrections);
+        let diff = unified_diff("./doc.txt", &old, &new);
+        assert!(diff.starts_with("--- a/doc.txt\n+++ b/doc.txt\n@@ -1,4 +1,4 @@\n-frist line\n"));
+        assert!(diff.contains("\n@@ -7,7 +7,7 @@\n line 7\n"));
+        assert!(diff.ends_with(
+            "\n@@ -17,4 +17,4 @@\n line 17\n line 18\n line 19\n\
+             -lsat line\n\\ No newline at end of file\n\
+             +last line\n\\ No newline at end of file\n"
+        ));
+
+        let dir = std::env::temp_dir().join(format!("autofix-diff-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("doc.txt"), &old).unwrap();
+        std::fs::write(dir.join("fix.patch"), &diff).unwrap();
+        let status = Command::new("git")
+            .args(["apply", "fix.patch"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        let applied = std::fs::read_to_string(dir.join("doc.txt")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(status.success());
+        assert_eq!(applied, new);
+
+        assert_eq!(unified_diff("doc.txt", &old, &old), "");
+    }
+}
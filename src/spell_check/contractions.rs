@@ -0,0 +1,167 @@
+use crate::spell_check::spell_checker::SpellChecker;
+
+// Apostrophe look-alikes typed by word processors and keyboards
+const APOSTROPHES: &[char] = &[
+    '\'', '\u{2019}', '\u{2018}', '\u{02bc}', '\u{2032}', '\u{ff07}',
+];
+
+// Contractions whose base is not a word on its own
+const IRREGULAR_CONTRACTIONS: &[(&str, &str)] = &[
+    ("can't", "cannot"),
+    ("won't", "will not"),
+    ("shan't", "shall not"),
+    ("ain't", "am not"),
+    ("y'all", "you all"),
+    ("o'clock", "of the clock"),
+    ("ma'am", "madam"),
+];
+
+// Suffixes split off a word, longest first; the base left over is checked on its own
+const SUFFIXES: &[&str] = &["n't", "'re", "'ll", "'ve", "'s", "'m", "'d", "s'", "'"];
+
+/// Accepts contractions and possessives ("don't", "dog's", "students'", also with
+/// curly apostrophes) when their base word is known to the wrapped checker, and
+/// suggests corrections for the base while keeping the suffix.
+pub struct ContractionAwareChecker<'a> {
+    inner: &'a dyn SpellChecker,
+}
+
+impl<'a> ContractionAwareChecker<'a> {
+    pub fn new(inner: &'a dyn SpellChecker) -> Self {
+        ContractionAwareChecker { inner }
+    }
+}
+
+impl SpellChecker for ContractionAwareChecker<'_> {
+    fn check_word(&self, word: &str) -> bool {
+        if self.inner.check_word(word) {
+            return true;
+        }
+
+        let normalized = normalize_apostrophes(word);
+        if normalized != word && self.inner.check_word(&normalized) {
+            return true;
+        }
+        if expand_irregular(&normalized).is_some() {
+            return true;
+        }
+
+        match split_suffix(word) {
+            Some((base, _)) => self.inner.check_word(&normalize_apostrophes(base)),
+            None => false,
+        }
+    }
+
+    fn suggest_correction(&self, word: &str) -> Vec<String> {
+        match split_suffix(word) {
+            Some((base, suffix)) => self
+                .inner
+                .suggest_correction(&normalize_apostrophes(base))
+                .into_iter()
+                .map(|suggestion| suggestion + suffix)
+                .collect(),
+            None => self.inner.suggest_correction(&normalize_apostrophes(word)),
+        }
+    }
+}
+
+pub fn normalize_apostrophes(word: &str) -> String {
+    word.replace(APOSTROPHES, "'")
+}
+
+/// Expansion of contractions such as "won't" that cannot be split into base and suffix.
+pub fn expand_irregular(word: &str) -> Option<&'static str> {
+    let word = normalize_apostrophes(word).to_lowercase();
+    IRREGULAR_CONTRACTIONS
+        .iter()
+        .find(|(contraction, _)| *contraction == word)
+        .map(|(_, expansion)| *expansion)
+}
+
+/// Splits `word` into its base and contraction or possessive suffix, with the suffix
+/// keeping whichever apostrophe the word was written with.
+pub fn split_suffix(word: &str) -> Option<(&str, &str)> {
+    let normalized = normalize_apostrophes(word).to_lowercase();
+    let suffix = SUFFIXES
+        .iter()
+        .find(|suffix| normalized.ends_with(*suffix) && normalized.len() > suffix.len())?;
+
+    // Count chars rather than bytes, since curly apostrophes are multibyte
+    let suffix_chars = suffix.chars().count();
+    let split = word.char_indices().rev().nth(suffix_chars - 1)?.0;
+    // "students'" keeps the plural: only the apostrophe is the suffix
+    let split = if *suffix == "s'" { split + 1 } else { split };
+    Some(word.split_at(split))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spell_check::hash_map_look_up::HashMapLookup;
+
+    #[test]
+    fn splits_suffixes() {
+        let cases = [
+            ("don't", Some(("do", "n't"))),
+            ("dog's", Some(("dog", "'s"))),
+            ("they're", Some(("they", "'re"))),
+            ("we'll", Some(("we", "'ll"))),
+            ("I'm", Some(("I", "'m"))),
+            ("students'", Some(("students", "'"))),
+            ("DOG'S", Some(("DOG", "'S"))),
+            // The suffix keeps the apostrophe it was written with
+            ("dog\u{2019}s", Some(("dog", "\u{2019}s"))),
+            ("don\u{2019}t", Some(("do", "n\u{2019}t"))),
+            ("dog\u{02bc}s", Some(("dog", "\u{02bc}s"))),
+            // A suffix alone has no base
+            ("'s", None),
+            ("'", None),
+            ("dogs", None),
+            ("rock'n'roll", None),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(split_suffix(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn expands_irregular_contractions() {
+        assert_eq!(expand_irregular("won't"), Some("will not"));
+        assert_eq!(expand_irregular("Can\u{2019}t"), Some("cannot"));
+        assert_eq!(expand_irregular("don't"), None);
+    }
+
+    #[test]
+    fn normalizes_apostrophes() {
+        assert_eq!(
+            normalize_apostrophes("it\u{2019}s \u{2018}x\u{ff07}"),
+            "it's 'x'"
+        );
+    }
+
+    fn dictionary() -> HashMapLookup {
+        let words = ["dog", "do", "they", "student", "students", "o'neill", "cat"];
+        HashMapLookup::new(words.into_iter().map(str::to_string).collect())
+    }
+
+    #[test]
+    fn checks_base_words() {
+        let dictionary = dictionary();
+        let checker = ContractionAwareChecker::new(&dictionary);
+        for word in [
+            "dog",
+            "dog's",
+            "dog\u{2019}s",
+            "don't",
+            "they're",
+            "students'",
+            "won't",
+            "o\u{2019}neill",
+        ] {
+            assert!(checker.check_word(word), "{}", word);
+        }
+        for word in ["dgo's", "cta", "'s", "dogs'"] {
+            assert!(!checker.check_word(word), "{}", word);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Levenshtein distance counted in extended grapheme clusters, so that "é" written
+/// as `e` + U+0301, "ж" and "👍🏽" each count as one character, whatever their size
+/// in bytes or scalar values.
+pub fn edit_distance(s1: &str, s2: &str) -> usize {
+    // Every ASCII byte is its own grapheme except in "\r\n"
+    if s1.is_ascii() && s2.is_ascii() && !s1.contains('\r') && !s2.contains('\r') {
+        return wagner_fischer(s1.as_bytes(), s2.as_bytes());
+    }
+    wagner_fischer(&graphemes(s1), &graphemes(s2))
+}
+
+pub fn graphemes(word: &str) -> Vec<&str> {
+    word.graphemes(true).collect()
+}
+
+// Wagner-Fischer over any sequence, keeping only the previous row of the matrix
+fn wagner_fischer<T: PartialEq>(s1: &[T], s2: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..=s2.len()).collect();
+    let mut current = vec![0; s2.len() + 1];
+
+    for (i, c1) in s1.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, c2) in s2.iter().enumerate() {
+            let cost = if c1 == c2 { 0 } else { 1 };
+            current[j + 1] = (previous[j + 1] + 1)
+                .min(current[j] + 1)
+                .min(previous[j] + cost);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[s2.len()]
+}
+
+/// Like `edit_distance`, but swapping two adjacent graphemes counts as a single edit,
+/// so that "recieve" is one edit from "receive" (optimal string alignment).
+pub fn transposition_distance(s1: &str, s2: &str) -> usize {
+    let (s1, s2) = (graphemes(s1), graphemes(s2));
+    let mut rows = vec![vec![0; s2.len() + 1]; s1.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=s1.len() {
+        for j in 1..=s2.len() {
+            let cost = if s1[i - 1] == s2[j - 1] { 0 } else { 1 };
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && s1[i - 1] == s2[j - 2] && s1[i - 2] == s2[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[s1.len()][s2.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn accented_latin() {
+        assert_eq!(edit_distance("café", "cafe"), 1);
+        // Decomposed "é" is one grapheme of two scalar values
+        assert_eq!(edit_distance("cafe\u{301}", "cafe"), 1);
+        assert_eq!(edit_distance("cafe\u{301}", "caf"), 1);
+    }
+
+    #[test]
+    fn cyrillic() {
+        assert_eq!(edit_distance("привет", "привт"), 1);
+        assert_eq!(edit_distance("привет", "превет"), 1);
+    }
+
+    #[test]
+    fn greek() {
+        assert_eq!(edit_distance("λόγος", "λογος"), 1);
+        assert_eq!(edit_distance("λόγος", "λόγοι"), 1);
+    }
+
+    #[test]
+    fn emoji() {
+        // Man, zero-width joiner, woman, zero-width joiner, girl
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(graphemes(family).len(), 1);
+        assert_eq!(edit_distance(&format!("a{}b", family), "ab"), 1);
+        assert_eq!(edit_distance(family, "\u{1f468}"), 1);
+        assert_eq!(edit_distance("👍🏽", "👍"), 1);
+    }
+
+    #[test]
+    fn transpositions() {
+        assert_eq!(transposition_distance("recieve", "receive"), 1);
+        assert_eq!(edit_distance("recieve", "receive"), 2);
+        assert_eq!(transposition_distance("привет", "пирвет"), 1);
+        assert_eq!(transposition_distance("cafe\u{301}", "caf\u{301}e"), 2);
+        assert_eq!(transposition_distance("ca", "abc"), 3);
+        assert_eq!(transposition_distance("", "ab"), 2);
+    }
+}
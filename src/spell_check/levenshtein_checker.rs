@@ -1,13 +1,22 @@
-use crate::spell_check::spell_checker::SpellChecker;
+use crate::spell_check::edit_distance::edit_distance;
+use crate::spell_check::spell_checker::{SpellChecker, SuggestionLimits};
 use std::collections::HashSet;
-use strsim::levenshtein;
 pub struct LevenshteinChecker {
     dictionary: HashSet<String>,
+    limits: SuggestionLimits,
 }
 
 impl LevenshteinChecker {
     pub fn new(dictionary: HashSet<String>) -> Self {
-        LevenshteinChecker { dictionary }
+        LevenshteinChecker {
+            dictionary,
+            limits: SuggestionLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: SuggestionLimits) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
@@ -20,17 +29,17 @@ impl SpellChecker for LevenshteinChecker {
         let mut suggestions = self
             .dictionary
             .iter()
-            .map(|dict_word| (dict_word, levenshtein(word, dict_word)))
-            .filter(|&(_, dist)| dist <= 2) // You can adjust the threshold
+            .map(|dict_word| (dict_word, edit_distance(word, dict_word)))
+            .filter(|&(_, dist)| dist <= self.limits.max_distance)
             .collect::<Vec<(&String, usize)>>();
 
         // Sort the suggestions by their distance
         suggestions.sort_by_key(|&(_, dist)| dist);
 
-        // Take the top 3 suggestions
+        // Take the top k suggestions
         suggestions
             .into_iter()
-            .take(3)
+            .take(self.limits.top_k)
             .map(|(word, _)| word.clone())
             .collect()
     }
@@ -0,0 +1,125 @@
+use crate::spell_check::spell_checker::SpellChecker;
+use crate::utils::read_dataset::{read_dataset_chunks, DatasetChunk};
+use crate::utils::token_filter::TokenFilter;
+use crate::utils::tokenizer::{Token, Tokenizer};
+use crate::utils::unicode_normalization::nfc;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::io;
+use std::ops::Range;
+
+/// One occurrence of an unknown word in the checked text.
+#[derive(Debug, Clone)]
+pub struct Misspelling {
+    /// The word exactly as written in the input
+    pub word: String,
+    pub range: Range<usize>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct CheckResult {
+    pub misspellings: Vec<Misspelling>,
+    /// Number of tokens checked, misspelled or not
+    pub tokens_checked: usize,
+}
+
+impl CheckResult {
+    /// Distinct misspelled words, normalized and lowercased the way the dictionary is.
+    pub fn unknown_words(&self) -> HashSet<String> {
+        self.misspellings
+            .iter()
+            .map(|misspelling| nfc(&misspelling.word).to_lowercase())
+            .collect()
+    }
+}
+
+/// Totals of a check whose misspellings are not kept, such as a streamed one.
+#[derive(Debug, Default)]
+pub struct CheckSummary {
+    /// Distinct misspelled words, as in `CheckResult::unknown_words`
+    pub unknown_words: HashSet<String>,
+    pub misspellings: usize,
+    pub tokens_checked: usize,
+}
+
+impl CheckSummary {
+    pub fn add(&mut self, result: &CheckResult) {
+        self.unknown_words.extend(result.unknown_words());
+        self.misspellings += result.misspellings.len();
+        self.tokens_checked += result.tokens_checked;
+    }
+}
+
+pub fn check_tokens(tokens: &[Token], checker: &dyn SpellChecker) -> CheckResult {
+    let misspellings = tokens
+        .par_iter()
+        .filter(|token| !checker.check_word(&nfc(token.text).to_lowercase()))
+        .map(|token| Misspelling {
+            word: token.text.to_string(),
+            range: token.range.clone(),
+            line: token.line,
+            column: token.column,
+        })
+        .collect();
+
+    CheckResult {
+        misspellings,
+        tokens_checked: tokens.len(),
+    }
+}
+
+/// Checks a plain text file chunk by chunk, tokenizing lazily, and hands each chunk
+/// with its misspellings to `emit` in file order, so that memory holds only the chunks
+/// in flight rather than every misspelling of the file.
+pub fn check_file_streaming(
+    file_path: &str,
+    chunk_size: usize,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+    mut emit: impl FnMut(&DatasetChunk, CheckResult) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut chunks = read_dataset_chunks(file_path, chunk_size)?;
+    // One chunk per thread is checked at a time
+    let batch_size = rayon::current_num_threads();
+    loop {
+        let batch = chunks
+            .by_ref()
+            .take(batch_size)
+            .collect::<io::Result<Vec<_>>>()?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let results = batch
+            .par_iter()
+            .map(|chunk| check_chunk(chunk, tokenizer, filter, checker))
+            .collect::<Vec<_>>();
+        for (chunk, result) in batch.iter().zip(results) {
+            emit(chunk, result)?;
+        }
+    }
+}
+
+fn check_chunk(
+    chunk: &DatasetChunk,
+    tokenizer: &dyn Tokenizer,
+    filter: &TokenFilter,
+    checker: &dyn SpellChecker,
+) -> CheckResult {
+    let mut result = CheckResult::default();
+    for token in filter.filter(&chunk.text, tokenizer.tokens(&chunk.text)) {
+        result.tokens_checked += 1;
+        if !checker.check_word(&nfc(token.text).to_lowercase()) {
+            // Chunks start at a line start, so only the line number needs shifting
+            result.misspellings.push(Misspelling {
+                word: token.text.to_string(),
+                range: chunk.offset + token.range.start..chunk.offset + token.range.end,
+                line: chunk.line + token.line - 1,
+                column: token.column,
+            });
+        }
+    }
+    result
+}
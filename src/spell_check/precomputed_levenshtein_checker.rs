@@ -1,57 +1,474 @@
-use std::collections::HashMap;
-use strsim::levenshtein;
+use crate::spell_check::edit_distance::{edit_distance, graphemes};
+use crate::spell_check::spell_checker::{SpellChecker, SuggestionLimits};
+use log::info;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
 
+pub const DEFAULT_GRAPH_DISTANCE: usize = 1;
+
+// Number of words whose neighbor lists are computed before being flushed to disk
+const BUILD_CHUNK_SIZE: usize = 16_384;
+
+const GRAPH_HEADER: &str = "# rust-spell-checker neighbor graph";
+
+/// Nearest-neighbor graph over the dictionary: every word is linked to the other
+/// dictionary words within `max_distance` edits of it.
 pub struct PrecomputedLevenshteinChecker {
-    dictionary: Vec<String>,
-    distances: HashMap<(String, String), usize>,
+    words: Vec<String>,
+    index: HashMap<String, usize>,
+    neighbors: Vec<Vec<(usize, usize)>>,
+    max_distance: usize,
+    // Every grapheme found in the dictionary, tried as insertions and substitutions
+    alphabet: Vec<String>,
+    limits: SuggestionLimits,
 }
 
 impl PrecomputedLevenshteinChecker {
-    fn check_word(&self, word: &str) -> bool {
-        self.dictionary.contains(&word.to_string())
+    pub fn with_distance(dictionary: Vec<String>, max_distance: usize) -> Self {
+        let words = dedup_words(dictionary);
+        let deletion_index = build_deletion_index(&words, max_distance);
+
+        let neighbors = (0..words.len())
+            .into_par_iter()
+            .map(|id| find_neighbors(&words, &deletion_index, id, max_distance))
+            .collect();
+
+        Self::from_parts(words, neighbors, max_distance)
     }
 
-    pub fn new(dictionary: Vec<String>) -> Self {
-        let mut distances = HashMap::new();
+    /// Builds the graph chunk by chunk and streams every finished chunk to `output_path`,
+    /// so only the deletion index and one chunk of neighbor lists are held in memory.
+    pub fn build_to_file(
+        dictionary: Vec<String>,
+        max_distance: usize,
+        output_path: &str,
+    ) -> io::Result<usize> {
+        let words = dedup_words(dictionary);
+        let deletion_index = build_deletion_index(&words, max_distance);
+        // `data/index/` does not exist on a fresh checkout
+        if let Some(dir) = Path::new(output_path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut writer = BufWriter::new(File::create(output_path)?);
+        let mut edges = 0;
 
-        for (i, word1) in dictionary.iter().enumerate() {
-            for word2 in dictionary[i + 1..].iter() {
-                let distance = levenshtein(word1, word2);
-                distances.insert((word1.clone(), word2.clone()), distance);
-                distances.insert((word2.clone(), word1.clone()), distance);
+        writeln!(writer, "{}", GRAPH_HEADER)?;
+        writeln!(writer, "# max_distance={}", max_distance)?;
+
+        for chunk_start in (0..words.len()).step_by(BUILD_CHUNK_SIZE) {
+            let chunk_end = (chunk_start + BUILD_CHUNK_SIZE).min(words.len());
+            let chunk_neighbors: Vec<_> = (chunk_start..chunk_end)
+                .into_par_iter()
+                .map(|id| find_neighbors(&words, &deletion_index, id, max_distance))
+                .collect();
+
+            for (id, neighbors) in (chunk_start..chunk_end).zip(chunk_neighbors) {
+                write!(writer, "{}", words[id])?;
+                for (neighbor, dist) in &neighbors {
+                    write!(writer, "\t{}:{}", words[*neighbor], dist)?;
+                }
+                writeln!(writer)?;
+                edges += neighbors.len();
             }
+
+            info!(
+                "Neighbor graph: {}/{} words written",
+                chunk_end,
+                words.len()
+            );
         }
 
+        writer.flush()?;
+        Ok(edges)
+    }
+
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        let reader = io::BufReader::new(File::open(file_path)?);
+        let mut max_distance = DEFAULT_GRAPH_DISTANCE;
+        let mut words = Vec::new();
+        let mut raw_neighbors = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(value) = comment.trim().strip_prefix("max_distance=") {
+                    max_distance = value.parse().map_err(invalid_data)?;
+                }
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let word = match fields.next() {
+                Some(word) if !word.is_empty() => word.to_string(),
+                _ => continue,
+            };
+            let neighbors = fields
+                .map(|field| {
+                    let (neighbor, dist) = field
+                        .rsplit_once(':')
+                        .ok_or_else(|| invalid_data(format!("malformed edge `{}`", field)))?;
+                    Ok((neighbor.to_string(), dist.parse().map_err(invalid_data)?))
+                })
+                .collect::<io::Result<Vec<(String, usize)>>>()?;
+
+            words.push(word);
+            raw_neighbors.push(neighbors);
+        }
+
+        let index: HashMap<&str, usize> = words
+            .iter()
+            .enumerate()
+            .map(|(id, word)| (word.as_str(), id))
+            .collect();
+        let neighbors = raw_neighbors
+            .into_iter()
+            .map(|edges| {
+                edges
+                    .into_iter()
+                    .filter_map(|(neighbor, dist)| {
+                        index.get(neighbor.as_str()).map(|&id| (id, dist))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self::from_parts(words, neighbors, max_distance))
+    }
+
+    fn from_parts(
+        words: Vec<String>,
+        neighbors: Vec<Vec<(usize, usize)>>,
+        max_distance: usize,
+    ) -> Self {
+        let index = words
+            .iter()
+            .enumerate()
+            .map(|(id, word)| (word.clone(), id))
+            .collect();
+        let alphabet = words
+            .iter()
+            .flat_map(|word| graphemes(word))
+            .collect::<HashSet<&str>>()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
         PrecomputedLevenshteinChecker {
-            dictionary,
-            distances,
+            words,
+            index,
+            neighbors,
+            max_distance,
+            alphabet,
+            limits: SuggestionLimits::default(),
         }
     }
 
-    pub fn suggest_correction(&self, word: &str) -> Vec<String> {
-        let mut suggestions = self
-            .dictionary
+    pub fn with_limits(mut self, limits: SuggestionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn max_distance(&self) -> usize {
+        self.max_distance
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.neighbors.iter().map(Vec::len).sum()
+    }
+
+    /// Dictionary words linked to `word` in the graph, closest first.
+    pub fn similar_words(&self, word: &str) -> Vec<(&str, usize)> {
+        let mut similar: Vec<_> = self
+            .index
+            .get(word)
+            .map(|&id| {
+                self.neighbors[id]
+                    .iter()
+                    .map(|&(neighbor, dist)| (self.words[neighbor].as_str(), dist))
+                    .collect()
+            })
+            .unwrap_or_default();
+        similar.sort_by_key(|&(word, dist)| (dist, word));
+        similar
+    }
+
+    // Cheap first pass: dictionary words exactly one edit away from `word`
+    fn single_edit_candidates(&self, word: &str) -> HashSet<usize> {
+        let graphemes = graphemes(word);
+        let mut edits = HashSet::new();
+
+        for i in 0..=graphemes.len() {
+            if i < graphemes.len() {
+                edits.insert(splice(&graphemes, i, 1, None));
+            }
+            if i + 1 < graphemes.len() {
+                let mut transposed = graphemes.clone();
+                transposed.swap(i, i + 1);
+                edits.insert(transposed.concat());
+            }
+            for grapheme in &self.alphabet {
+                if i < graphemes.len() {
+                    edits.insert(splice(&graphemes, i, 1, Some(grapheme)));
+                }
+                edits.insert(splice(&graphemes, i, 0, Some(grapheme)));
+            }
+        }
+
+        edits
+            .iter()
+            .filter_map(|edit: &String| self.index.get(edit).copied())
+            .collect()
+    }
+}
+
+impl SpellChecker for PrecomputedLevenshteinChecker {
+    fn check_word(&self, word: &str) -> bool {
+        self.index.contains_key(word)
+    }
+
+    // Words more than one edit away are only found through a dictionary word one edit
+    // from `word`, linked to them in the graph
+    fn suggest_correction(&self, word: &str) -> Vec<String> {
+        let first_pass = self.single_edit_candidates(word);
+
+        // Expand every first-pass hit with its graph neighbors
+        let candidates: HashSet<usize> = first_pass
             .iter()
-            .map(|dict_word| {
-                (
-                    dict_word,
-                    *self
-                        .distances
-                        .get(&(word.to_string(), dict_word.clone()))
-                        .unwrap_or(&usize::MAX),
-                )
+            .flat_map(|&id| {
+                std::iter::once(id).chain(self.neighbors[id].iter().map(|&(neighbor, _)| neighbor))
             })
-            .filter(|&(_, dist)| dist <= 2) // You can adjust the threshold
+            .collect();
+
+        let mut suggestions = candidates
+            .into_iter()
+            .map(|id| (&self.words[id], edit_distance(word, &self.words[id])))
+            .filter(|&(_, dist)| dist <= self.limits.max_distance)
             .collect::<Vec<(&String, usize)>>();
 
         // Sort the suggestions by their distance
-        suggestions.sort_by_key(|&(_, dist)| dist);
+        suggestions.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
 
-        // Take the top 3 suggestions
+        // Take the top k suggestions
         suggestions
             .into_iter()
-            .take(3)
+            .take(self.limits.top_k)
             .map(|(word, _)| word.clone())
             .collect()
     }
 }
+
+fn dedup_words(dictionary: Vec<String>) -> Vec<String> {
+    let mut words = dictionary;
+    words.sort();
+    words.dedup();
+    words
+}
+
+// Maps every string reachable by up to `depth` grapheme deletions to the words producing it.
+// Two words within `depth` edits of each other always share at least one such key.
+fn build_deletion_index(words: &[String], depth: usize) -> HashMap<String, Vec<usize>> {
+    words
+        .par_iter()
+        .enumerate()
+        .fold(
+            HashMap::new,
+            |mut index: HashMap<String, Vec<usize>>, (id, word)| {
+                for key in deletions(word, depth) {
+                    index.entry(key).or_default().push(id);
+                }
+                index
+            },
+        )
+        .reduce(HashMap::new, |mut left, right| {
+            for (key, ids) in right {
+                left.entry(key).or_default().extend(ids);
+            }
+            left
+        })
+}
+
+fn find_neighbors(
+    words: &[String],
+    deletion_index: &HashMap<String, Vec<usize>>,
+    id: usize,
+    max_distance: usize,
+) -> Vec<(usize, usize)> {
+    let word = &words[id];
+    let candidates: HashSet<usize> = deletions(word, max_distance)
+        .iter()
+        .filter_map(|key| deletion_index.get(key))
+        .flatten()
+        .copied()
+        .filter(|&candidate| candidate != id)
+        .collect();
+
+    let mut neighbors: Vec<_> = candidates
+        .into_iter()
+        .map(|candidate| (candidate, edit_distance(word, &words[candidate])))
+        .filter(|&(_, dist)| dist <= max_distance)
+        .collect();
+    neighbors.sort_unstable();
+    neighbors
+}
+
+fn deletions(word: &str, depth: usize) -> HashSet<String> {
+    let mut result = HashSet::from([word.to_string()]);
+    let mut frontier = vec![word.to_string()];
+
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for current in &frontier {
+            let graphemes = graphemes(current);
+            for i in 0..graphemes.len() {
+                let deleted = splice(&graphemes, i, 1, None);
+                if result.insert(deleted.clone()) {
+                    next.push(deleted);
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    result
+}
+
+fn splice(graphemes: &[&str], at: usize, remove: usize, insert: Option<&str>) -> String {
+    graphemes[..at]
+        .iter()
+        .copied()
+        .chain(insert)
+        .chain(graphemes[at + remove..].iter().copied())
+        .collect()
+}
+
+fn invalid_data<E: ToString>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: &[&str] = &[
+        "cat", "cut", "hut", "hat", "bat", "car", "cart", "card", "dog", "café",
+    ];
+
+    fn dictionary() -> Vec<String> {
+        WORDS.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn limits(max_distance: usize) -> SuggestionLimits {
+        SuggestionLimits {
+            max_distance,
+            top_k: WORDS.len(),
+        }
+    }
+
+    // Every dictionary word within `max_distance`, closest first
+    fn brute_force(word: &str, max_distance: usize) -> Vec<String> {
+        let mut suggestions = WORDS
+            .iter()
+            .map(|candidate| (edit_distance(word, candidate), candidate.to_string()))
+            .filter(|&(dist, _)| dist <= max_distance)
+            .collect::<Vec<_>>();
+        suggestions.sort();
+        suggestions.into_iter().map(|(_, word)| word).collect()
+    }
+
+    #[test]
+    fn links_words_within_distance() {
+        let graph = PrecomputedLevenshteinChecker::with_distance(dictionary(), 1);
+        assert_eq!(graph.max_distance(), 1);
+        assert_eq!(
+            graph.similar_words("cat"),
+            [("bat", 1), ("car", 1), ("cart", 1), ("cut", 1), ("hat", 1)]
+        );
+        assert_eq!(graph.similar_words("dog"), []);
+        assert_eq!(graph.similar_words("cafe"), []);
+
+        let graph = PrecomputedLevenshteinChecker::with_distance(dictionary(), 2);
+        assert!(graph.similar_words("cat").contains(&("card", 2)));
+        assert!(graph.similar_words("café").contains(&("car", 2)));
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("neighbor-graph-test-{}", std::process::id()));
+        let file_path = dir.join("index").join("neighbors.tsv");
+        let file_path = file_path.to_str().unwrap();
+
+        let mut dictionary = dictionary();
+        dictionary.push("cat".to_string());
+        let edges = PrecomputedLevenshteinChecker::build_to_file(dictionary, 2, file_path).unwrap();
+        let loaded = PrecomputedLevenshteinChecker::load(file_path);
+        fs::remove_dir_all(&dir).unwrap();
+        let loaded = loaded.unwrap();
+
+        let built = PrecomputedLevenshteinChecker::with_distance(self::dictionary(), 2);
+        assert_eq!(edges, built.edge_count());
+        assert_eq!(loaded.edge_count(), built.edge_count());
+        assert_eq!(loaded.max_distance(), 2);
+        for word in WORDS {
+            assert!(loaded.check_word(word));
+            assert_eq!(loaded.similar_words(word), built.similar_words(word));
+        }
+    }
+
+    #[test]
+    fn load_rejects_malformed_edges() {
+        let file_path =
+            std::env::temp_dir().join(format!("neighbor-graph-bad-{}.tsv", std::process::id()));
+        fs::write(&file_path, "# max_distance=1\ncat\tbat\n").unwrap();
+        let loaded = PrecomputedLevenshteinChecker::load(file_path.to_str().unwrap());
+        fs::remove_file(&file_path).unwrap();
+        assert_eq!(loaded.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn suggestions_match_edit_distance() {
+        // Every word two edits from these has a dictionary word one edit away between
+        for word in ["cst", "cet", "crt", "dg", "hats"] {
+            let graph = PrecomputedLevenshteinChecker::with_distance(dictionary(), 1)
+                .with_limits(limits(2));
+            assert_eq!(
+                graph.suggest_correction(word),
+                brute_force(word, 2),
+                "{}",
+                word
+            );
+        }
+        // A graph linking words two edits apart reaches further from the first pass
+        for word in ["cst", "cet", "crt", "dg", "hats", "cats", "cafe"] {
+            let graph = PrecomputedLevenshteinChecker::with_distance(dictionary(), 2)
+                .with_limits(limits(2));
+            assert_eq!(
+                graph.suggest_correction(word),
+                brute_force(word, 2),
+                "{}",
+                word
+            );
+        }
+        // Without a dictionary word in between, only the first pass is found
+        let graph =
+            PrecomputedLevenshteinChecker::with_distance(dictionary(), 1).with_limits(limits(2));
+        assert_eq!(graph.suggest_correction("cafe"), ["café"]);
+        assert!(!graph
+            .suggest_correction("cats")
+            .contains(&"card".to_string()));
+    }
+
+    #[test]
+    fn suggestions_respect_limits() {
+        let graph = PrecomputedLevenshteinChecker::with_distance(dictionary(), 1).with_limits(
+            SuggestionLimits {
+                max_distance: 1,
+                top_k: 2,
+            },
+        );
+        assert_eq!(graph.suggest_correction("cst"), ["cat", "cut"]);
+        assert_eq!(graph.suggest_correction("xyz"), Vec::<String>::new());
+    }
+}
@@ -2,3 +2,19 @@ pub trait SpellChecker: Sync {
     fn check_word(&self, word: &str) -> bool;
     fn suggest_correction(&self, word: &str) -> Vec<String>;
 }
+
+/// How far and how many suggestions a distance-based checker returns.
+#[derive(Debug, Clone, Copy)]
+pub struct SuggestionLimits {
+    pub max_distance: usize,
+    pub top_k: usize,
+}
+
+impl Default for SuggestionLimits {
+    fn default() -> Self {
+        SuggestionLimits {
+            max_distance: 2,
+            top_k: 3,
+        }
+    }
+}
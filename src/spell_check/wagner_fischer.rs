@@ -1,22 +1,13 @@
-use crate::spell_check::spell_checker::SpellChecker;
-use cudarc::*;
+use crate::spell_check::edit_distance::edit_distance;
+use crate::spell_check::spell_checker::{SpellChecker, SuggestionLimits};
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::ffi::CString;
-use std::ffi::{c_char, c_int};
 use std::sync::Mutex;
 
-extern "C" {
-    fn suggest_corrections_kernel(
-        unknown_words: *const *const c_char,
-        corrections: *mut *mut c_char,
-        num_words: c_int,
-    );
-}
-
 pub struct WagnerFischerChecker {
     dictionary: HashSet<String>,
     cache: Mutex<HashMap<(String, String), usize>>,
+    limits: SuggestionLimits,
 }
 
 impl WagnerFischerChecker {
@@ -24,9 +15,15 @@ impl WagnerFischerChecker {
         WagnerFischerChecker {
             dictionary,
             cache: Mutex::new(HashMap::new()),
+            limits: SuggestionLimits::default(),
         }
     }
 
+    pub fn with_limits(mut self, limits: SuggestionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     fn wagner_fischer(&self, s1: &str, s2: &str) -> usize {
         let mut cache = self.cache.lock().unwrap();
 
@@ -35,31 +32,7 @@ impl WagnerFischerChecker {
             return result;
         }
 
-        let mut matrix = vec![vec![0; s2.len() + 1]; s1.len() + 1];
-
-        for i in 1..=s1.len() {
-            matrix[i][0] = i;
-        }
-
-        for j in 1..=s2.len() {
-            matrix[0][j] = j;
-        }
-
-        for (i, c1) in s1.chars().enumerate() {
-            for (j, c2) in s2.chars().enumerate() {
-                let cost = if c1 == c2 { 0 } else { 1 };
-                matrix[i + 1][j + 1] = *[
-                    matrix[i][j + 1] + 1,
-                    matrix[i + 1][j] + 1,
-                    matrix[i][j] + cost,
-                ]
-                .iter()
-                .min()
-                .unwrap();
-            }
-        }
-
-        let result = matrix[s1.len()][s2.len()];
+        let result = edit_distance(s1, s2);
 
         // Store the result in the cache before returning it
         cache.insert((s1.to_string(), s2.to_string()), result);
@@ -78,73 +51,36 @@ impl SpellChecker for WagnerFischerChecker {
             .dictionary
             .par_iter()
             .map(|dict_word| (dict_word, self.wagner_fischer(word, dict_word)))
-            .filter(|&(_, dist)| dist <= 2) // You can adjust the threshold
+            .filter(|&(_, dist)| dist <= self.limits.max_distance)
             .collect();
 
-        // Convert the suggestions to Vec<CString>
-        let c_suggestions: Vec<CString> = suggestions
-            .iter()
-            .map(|(word, _)| CString::new(word.as_str()).unwrap())
-            .collect();
+        // Sort the suggestions by their distance, then alphabetically for a stable order
+        suggestions.sort_by(|(word1, dist1), (word2, dist2)| (dist1, word1).cmp(&(dist2, word2)));
 
-        // Convert the Vec<CString> to Vec<*const c_char>
-        let p_suggestions: Vec<*const c_char> =
-            c_suggestions.iter().map(|cstr| cstr.as_ptr()).collect();
-
-        // Convert the Vec<*const c_char> to DeviceBuffer<*const c_char>
-        // let device_suggestions = rustacuda::memory::DeviceBuffer::from_slice(&p_suggestions)?;
-        //
-        // // Allocate memory on the GPU for the corrections
-        // let mut device_corrections = rustacuda::memory::DeviceBuffer::from_slice(&vec![0; suggestions.len()])?;
-
-        // Define the grid and block size for the CUDA kernel
-        let grid_size = (suggestions.len() + 255) / 256;
-        let block_size = 256;
-
-        // Launch the CUDA kernel
-        // unsafe {
-        //     suggest_corrections_kernel(
-        //         device_suggestions.as_ptr() as *const *const c_char,
-        //         device_corrections.as_device_ptr().as_raw_mut() as *mut *mut c_char,
-        //         suggestions.len() as c_int);
-        // }
-
-        // Copy the corrections from the GPU to the CPU
-        let mut corrections = vec![0; suggestions.len()];
-        // device_corrections.copy_to(&mut corrections).unwrap();
-
-        // Combine the words and their corrections into a vector of tuples
-        let mut corrections: Vec<_> = suggestions
+        // Take the top k suggestions
+        suggestions
             .into_iter()
-            .zip(corrections.into_iter())
-            .collect();
+            .take(self.limits.top_k)
+            .map(|(word, _)| word.clone())
+            .collect()
+    }
+}
 
-        // Sort the corrections by their distance
-        corrections.sort_by_key(|&(_, dist)| dist);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Take the top 3 corrections
-        corrections
+    #[test]
+    fn suggests_the_closest_words_first() {
+        let dictionary = ["cat", "cart", "cast", "dog", "act", "scatter"]
             .into_iter()
-            .take(3)
-            .map(|((word, _), _)| word.clone())
-            .collect::<Vec<String>>()
+            .map(String::from)
+            .collect();
+        let checker = WagnerFischerChecker::new(dictionary).with_limits(SuggestionLimits {
+            max_distance: 2,
+            top_k: 3,
+        });
+        assert_eq!(checker.suggest_correction("cot"), ["cat", "act", "cart"]);
+        assert!(checker.suggest_correction("zzzzzz").is_empty());
     }
 }
-
-//CPU
-
-// fn suggest_correction(&self, word: &str) -> Vec<String> {
-//     let mut suggestions: Vec<_> = self.dictionary.par_iter()
-//         .map(|dict_word| (dict_word, self.wagner_fischer(word, dict_word)))
-//         .filter(|&(_, dist)| dist <= 2)  // You can adjust the threshold
-//         .collect();
-//
-//     // Sort the suggestions by their distance
-//     suggestions.sort_by_key(|&(_, dist)| dist);
-//
-//     // Take the top 3 suggestions
-//     suggestions.into_iter()
-//         .take(3)
-//         .map(|(word, _)| word.clone())
-//         .collect()
-// }
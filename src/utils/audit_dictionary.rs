@@ -0,0 +1,307 @@
+use crate::spell_check::precomputed_levenshtein_checker::PrecomputedLevenshteinChecker;
+use crate::utils::load_dictionary::read_dictionary_lines;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead};
+
+// An entry is a suspected typo when a neighbor is this many times more frequent
+pub const DEFAULT_FREQUENCY_RATIO: u64 = 100;
+
+pub enum AuditIssue {
+    NearDuplicate {
+        frequent_word: String,
+        word_frequency: u64,
+        frequent_frequency: u64,
+    },
+    NonLetterCharacters(String),
+    StrayWhitespace,
+    MixedScripts(Vec<&'static str>),
+    CaseVariants(Vec<String>),
+    InvalidUtf8,
+}
+
+pub struct AuditFinding {
+    pub line: usize,
+    pub word: String,
+    pub issue: AuditIssue,
+}
+
+impl fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.issue {
+            AuditIssue::NearDuplicate {
+                frequent_word,
+                word_frequency,
+                frequent_frequency,
+            } => write!(
+                f,
+                "{:?} (seen {}x) is one edit from {:?} (seen {}x)",
+                self.word, word_frequency, frequent_word, frequent_frequency
+            ),
+            AuditIssue::NonLetterCharacters(characters) => {
+                write!(
+                    f,
+                    "{:?} contains non-letter characters {:?}",
+                    self.word, characters
+                )
+            }
+            AuditIssue::StrayWhitespace => {
+                write!(f, "{:?} has leading or trailing whitespace", self.word)
+            }
+            AuditIssue::MixedScripts(scripts) => {
+                write!(f, "{:?} mixes scripts {}", self.word, scripts.join(", "))
+            }
+            AuditIssue::CaseVariants(variants) => {
+                write!(
+                    f,
+                    "{:?} has case variants {}",
+                    self.word,
+                    variants.join(", ")
+                )
+            }
+            AuditIssue::InvalidUtf8 => write!(f, "invalid UTF-8, entry ignored on load"),
+        }
+    }
+}
+
+/// Word frequencies from a list of `word count` lines.
+pub fn load_frequencies(file_path: &str) -> io::Result<HashMap<String, u64>> {
+    let reader = io::BufReader::new(File::open(file_path)?);
+    let mut frequencies = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let mut fields = line.split_whitespace();
+        if let (Some(word), Some(count)) = (fields.next(), fields.next()) {
+            if let Ok(count) = count.parse::<u64>() {
+                *frequencies.entry(word.to_lowercase()).or_insert(0) += count;
+            }
+        }
+    }
+
+    Ok(frequencies)
+}
+
+pub fn audit_dictionary(
+    file_path: &str,
+    frequencies: Option<&HashMap<String, u64>>,
+    frequency_ratio: u64,
+) -> io::Result<Vec<AuditFinding>> {
+    let lines = read_dictionary_lines(file_path)?;
+    let mut findings: Vec<AuditFinding> = lines
+        .invalid_lines
+        .iter()
+        .map(|&line| AuditFinding {
+            line,
+            word: String::new(),
+            issue: AuditIssue::InvalidUtf8,
+        })
+        .collect();
+
+    let mut case_variants: BTreeMap<String, Vec<(usize, &str)>> = BTreeMap::new();
+
+    for (line, word) in &lines.entries {
+        let finding = |issue| AuditFinding {
+            line: *line,
+            word: word.clone(),
+            issue,
+        };
+
+        if word.trim() != word {
+            findings.push(finding(AuditIssue::StrayWhitespace));
+        }
+
+        let non_letters: String = word
+            .trim()
+            .chars()
+            .filter(|&c| !c.is_alphabetic() && c != '\'')
+            .collect();
+        if !non_letters.is_empty() {
+            findings.push(finding(AuditIssue::NonLetterCharacters(non_letters)));
+        }
+
+        let scripts = scripts_of(word);
+        if scripts.len() > 1 {
+            findings.push(finding(AuditIssue::MixedScripts(scripts)));
+        }
+
+        case_variants
+            .entry(word.trim().to_lowercase())
+            .or_default()
+            .push((*line, word));
+    }
+
+    for variants in case_variants.values().filter(|variants| variants.len() > 1) {
+        let (line, word) = variants[0];
+        findings.push(AuditFinding {
+            line,
+            word: word.to_string(),
+            issue: AuditIssue::CaseVariants(
+                variants[1..]
+                    .iter()
+                    .map(|(line, variant)| format!("{:?} (line {})", variant, line))
+                    .collect(),
+            ),
+        });
+    }
+
+    if let Some(frequencies) = frequencies {
+        let first_lines: HashMap<String, usize> = case_variants
+            .iter()
+            .map(|(word, variants)| (word.clone(), variants[0].0))
+            .collect();
+        findings.extend(find_near_duplicates(
+            &first_lines,
+            frequencies,
+            frequency_ratio,
+        ));
+    }
+
+    findings.sort_by_key(|finding| finding.line);
+    Ok(findings)
+}
+
+// `first_lines` maps every lowercased entry to the line it first appears on
+fn find_near_duplicates(
+    first_lines: &HashMap<String, usize>,
+    frequencies: &HashMap<String, u64>,
+    frequency_ratio: u64,
+) -> Vec<AuditFinding> {
+    let graph =
+        PrecomputedLevenshteinChecker::with_distance(first_lines.keys().cloned().collect(), 1);
+    let frequency = |word: &str| frequencies.get(word).copied().unwrap_or(0);
+
+    first_lines
+        .iter()
+        .filter_map(|(word, &line)| {
+            let word_frequency = frequency(word);
+            graph
+                .similar_words(word)
+                .into_iter()
+                .map(|(neighbor, _)| (neighbor, frequency(neighbor)))
+                .filter(|&(_, neighbor_frequency)| {
+                    neighbor_frequency >= word_frequency.max(1) * frequency_ratio
+                })
+                .max_by_key(|&(_, neighbor_frequency)| neighbor_frequency)
+                .map(|(neighbor, neighbor_frequency)| AuditFinding {
+                    line,
+                    word: word.clone(),
+                    issue: AuditIssue::NearDuplicate {
+                        frequent_word: neighbor.to_string(),
+                        word_frequency,
+                        frequent_frequency: neighbor_frequency,
+                    },
+                })
+        })
+        .collect()
+}
+
+fn scripts_of(word: &str) -> Vec<&'static str> {
+    let mut scripts = Vec::new();
+    for script in word.chars().filter(|c| c.is_alphabetic()).map(script_of) {
+        if !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+fn script_of(c: char) -> &'static str {
+    match c as u32 {
+        0x0041..=0x024F | 0x1E00..=0x1EFF => "Latin",
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => "Greek",
+        0x0400..=0x052F => "Cyrillic",
+        0x0590..=0x05FF => "Hebrew",
+        0x0600..=0x06FF => "Arabic",
+        _ => "Other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Audits `contents` written to a dictionary file of its own
+    fn audit(
+        name: &str,
+        contents: &[u8],
+        frequencies: Option<&HashMap<String, u64>>,
+    ) -> Vec<String> {
+        let file_path =
+            std::env::temp_dir().join(format!("audit-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&file_path, contents).unwrap();
+        let findings = audit_dictionary(file_path.to_str().unwrap(), frequencies, 100);
+        std::fs::remove_file(&file_path).unwrap();
+        findings.unwrap().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn flags_malformed_entries() {
+        let contents = "apple\n banana\ncherry2\nit's\nnaïve\nрyccкий\n\nhello world\r\n";
+        assert_eq!(
+            audit("malformed", contents.as_bytes(), None),
+            [
+                r#"line 2: " banana" has leading or trailing whitespace"#,
+                r#"line 3: "cherry2" contains non-letter characters "2""#,
+                r#"line 6: "рyccкий" mixes scripts Cyrillic, Latin"#,
+                r#"line 8: "hello world" contains non-letter characters " ""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_invalid_utf8() {
+        assert_eq!(
+            audit("utf8", b"apple\n\xff\xfe\npear\n", None),
+            ["line 2: invalid UTF-8, entry ignored on load"]
+        );
+    }
+
+    #[test]
+    fn flags_case_variants() {
+        assert_eq!(
+            audit("case", "Paris\nparis\nlondon\nPARIS\n".as_bytes(), None),
+            [r#"line 1: "Paris" has case variants "paris" (line 2), "PARIS" (line 4)"#]
+        );
+    }
+
+    #[test]
+    fn flags_rare_near_duplicates() {
+        let frequencies = [
+            ("definitely", 50_000),
+            ("definately", 3),
+            ("bat", 80_000),
+            ("cat", 900),
+            ("recieve", 1),
+            ("receive", 50_000),
+            ("colour", 100),
+        ]
+        .into_iter()
+        .map(|(word, count)| (word.to_string(), count))
+        .collect::<HashMap<_, _>>();
+        let contents = "definitely\nDefinately\nbat\ncat\nreceive\nrecieve\ncolour\ncolours\n";
+        assert_eq!(
+            audit("duplicates", contents.as_bytes(), Some(&frequencies)),
+            [
+                r#"line 2: "definately" (seen 3x) is one edit from "definitely" (seen 50000x)"#,
+                // Unseen words count as seen once
+                r#"line 8: "colours" (seen 0x) is one edit from "colour" (seen 100x)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn loads_frequencies() {
+        let file_path =
+            std::env::temp_dir().join(format!("frequencies-{}.txt", std::process::id()));
+        std::fs::write(&file_path, "the 100\nThe 20\nbad line\nof x\nand 7 extra\n").unwrap();
+        let frequencies = load_frequencies(file_path.to_str().unwrap());
+        std::fs::remove_file(&file_path).unwrap();
+        let frequencies = frequencies.unwrap();
+        assert_eq!(frequencies.len(), 2);
+        assert_eq!(frequencies["the"], 120);
+        assert_eq!(frequencies["and"], 7);
+    }
+}
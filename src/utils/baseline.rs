@@ -0,0 +1,356 @@
+use crate::utils::io::Finding;
+use crate::utils::unicode_normalization::nfc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+
+const BASELINE_VERSION: u32 = 1;
+
+/// A known finding, identified without its line number so that it survives edits
+/// elsewhere in the file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub word: String,
+    /// FNV-1a hash of the word and of the trimmed line it is on, in hex
+    pub fingerprint: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    findings: Vec<BaselineEntry>,
+}
+
+/// Findings accepted when a project started checking, so that only new ones are reported.
+///
+/// The same word on identical lines gives identical entries, so entries are counted:
+/// each occurrence in the baseline excuses one finding.
+#[derive(Default)]
+pub struct Baseline {
+    entries: BTreeMap<BaselineEntry, usize>,
+}
+
+impl Baseline {
+    pub fn new() -> Self {
+        Baseline::default()
+    }
+
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        let contents = fs::read_to_string(file_path)?;
+        let file: BaselineFile = serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if file.version != BASELINE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported baseline version {}", file.version),
+            ));
+        }
+        let mut baseline = Baseline::new();
+        for entry in file.findings {
+            baseline.insert(entry);
+        }
+        Ok(baseline)
+    }
+
+    /// Writes the entries sorted, so that the file diffs well under version control.
+    pub fn save(&self, file_path: &str) -> io::Result<()> {
+        let findings = self
+            .entries
+            .iter()
+            .flat_map(|(entry, &count)| std::iter::repeat_n(entry.clone(), count))
+            .collect();
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            findings,
+        };
+        let mut contents = serde_json::to_string_pretty(&file)?;
+        contents.push('\n');
+        fs::write(file_path, contents)
+    }
+
+    /// Number of findings, counting repeated entries.
+    pub fn count(&self) -> usize {
+        self.entries.values().sum()
+    }
+
+    pub fn insert(&mut self, entry: BaselineEntry) {
+        *self.entries.entry(entry).or_insert(0) += 1;
+    }
+
+    /// Uses up one occurrence of `entry`; false when the baseline has none left.
+    pub fn remove(&mut self, entry: &BaselineEntry) -> bool {
+        match self.entries.get_mut(entry) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What `check` does with a baseline file.
+pub enum BaselineMode {
+    Off,
+    /// Findings are recorded instead of reported
+    Write(Baseline),
+    /// Only findings missing from the baseline are reported
+    Filter(Baseline),
+}
+
+impl BaselineMode {
+    /// The findings of `file_path` left to report.
+    pub fn apply(&mut self, file_path: &str, findings: Vec<Finding>) -> io::Result<Vec<Finding>> {
+        if let BaselineMode::Off = self {
+            return Ok(findings);
+        }
+        let entries = baseline_entries(file_path, &findings)?;
+        Ok(self.apply_entries(findings, entries))
+    }
+
+    /// Like `apply`, for findings of `text`, which starts at line `first_line` of
+    /// `file_path`: a chunk of the file, or the version of it that was checked.
+    pub fn apply_to_text(
+        &mut self,
+        file_path: &str,
+        text: &str,
+        first_line: usize,
+        findings: Vec<Finding>,
+    ) -> Vec<Finding> {
+        if let BaselineMode::Off = self {
+            return findings;
+        }
+        let entries = text_baseline_entries(file_path, text, first_line, &findings);
+        self.apply_entries(findings, entries)
+    }
+
+    fn apply_entries(
+        &mut self,
+        findings: Vec<Finding>,
+        entries: Vec<BaselineEntry>,
+    ) -> Vec<Finding> {
+        match self {
+            BaselineMode::Off => findings,
+            BaselineMode::Write(baseline) => {
+                for entry in entries {
+                    baseline.insert(entry);
+                }
+                Vec::new()
+            }
+            BaselineMode::Filter(baseline) => findings
+                .into_iter()
+                .zip(entries)
+                .filter(|(_, entry)| !baseline.remove(entry))
+                .map(|(finding, _)| finding)
+                .collect(),
+        }
+    }
+}
+
+/// Baseline entries of `findings`, all of `file_path`, whose lines are read back from it.
+pub fn baseline_entries(file_path: &str, findings: &[Finding]) -> io::Result<Vec<BaselineEntry>> {
+    let line_numbers = findings
+        .iter()
+        .map(|finding| finding.line)
+        .collect::<BTreeSet<_>>();
+    let mut lines = HashMap::new();
+    if let Some(&last) = line_numbers.last() {
+        let reader = BufReader::new(File::open(file_path)?);
+        for (i, line) in reader.lines().take(last).enumerate() {
+            let line = line?;
+            if line_numbers.contains(&(i + 1)) {
+                lines.insert(i + 1, line);
+            }
+        }
+    }
+    Ok(entries(file_path, findings, |number| {
+        lines.get(&number).map(String::as_str)
+    }))
+}
+
+/// Baseline entries of `findings` in `text`, which starts at line `first_line` of
+/// `file_path`.
+pub fn text_baseline_entries(
+    file_path: &str,
+    text: &str,
+    first_line: usize,
+    findings: &[Finding],
+) -> Vec<BaselineEntry> {
+    let lines = text.lines().collect::<Vec<_>>();
+    entries(file_path, findings, |number| {
+        number
+            .checked_sub(first_line)
+            .and_then(|i| lines.get(i).copied())
+    })
+}
+
+// Entries of `findings`, given the text of each line by its number
+fn entries<'a>(
+    file_path: &str,
+    findings: &[Finding],
+    line: impl Fn(usize) -> Option<&'a str>,
+) -> Vec<BaselineEntry> {
+    // `./docs/a.md` and `docs/a.md` are the same file
+    let file = file_path.strip_prefix("./").unwrap_or(file_path);
+    findings
+        .iter()
+        .map(|finding| {
+            let word = nfc(&finding.word).to_lowercase();
+            let line = line(finding.line).map_or("", str::trim);
+            let mut hash = fnv1a(FNV_OFFSET_BASIS, word.as_bytes());
+            hash = fnv1a(hash, &[0]);
+            hash = fnv1a(hash, line.as_bytes());
+            BaselineEntry {
+                file: file.to_string(),
+                word,
+                fingerprint: format!("{:016x}", hash),
+            }
+        })
+        .collect()
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// 64-bit FNV-1a, stable across platforms and releases unlike `DefaultHasher`
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // A file in the temporary directory, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+            fs::write(&path, contents).unwrap();
+            TempFile(path)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+
+        fn write(&self, contents: &str) {
+            fs::write(&self.0, contents).unwrap();
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn finding(line: usize, word: &str) -> Finding {
+        Finding {
+            file: String::new(),
+            line,
+            column: 1,
+            word: word.to_string(),
+            suggestions: Vec::new(),
+            checker: "test".to_string(),
+        }
+    }
+
+    // The findings of every line of `text` starting with a word in `words`
+    fn findings(text: &str, words: &[&str]) -> Vec<Finding> {
+        text.lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let word = line.split_whitespace().next()?;
+                words.contains(&word).then(|| finding(i + 1, word))
+            })
+            .collect()
+    }
+
+    fn recorded(mode: BaselineMode) -> Baseline {
+        match mode {
+            BaselineMode::Write(baseline) => baseline,
+            _ => unreachable!(),
+        }
+    }
+
+    fn words(findings: &[Finding]) -> Vec<(usize, &str)> {
+        findings
+            .iter()
+            .map(|finding| (finding.line, finding.word.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_findings_that_moved() {
+        let old = "# Title\nteh cat\n";
+        let file = TempFile::new("baseline-moved.md", old);
+        let mut write = BaselineMode::Write(Baseline::new());
+        assert!(write
+            .apply(file.path(), findings(old, &["teh"]))
+            .unwrap()
+            .is_empty());
+        let mut filter = BaselineMode::Filter(recorded(write));
+
+        // Lines added above move the finding down; a new misspelling is reported
+        let new = "# Title\n\nIntro\n  teh cat\nteh dog\n";
+        file.write(new);
+        let reported = filter.apply(file.path(), findings(new, &["teh"])).unwrap();
+        assert_eq!(words(&reported), [(5, "teh")]);
+    }
+
+    #[test]
+    fn counts_duplicate_findings() {
+        let old = "wrod here\nwrod here\n";
+        let file = TempFile::new("baseline-duplicates.md", old);
+        let mut write = BaselineMode::Write(Baseline::new());
+        write.apply(file.path(), findings(old, &["wrod"])).unwrap();
+        let baseline = recorded(write);
+        assert_eq!(baseline.count(), 2);
+
+        // Counts survive a round trip through the file
+        let saved = TempFile::new("baseline-duplicates.json", "");
+        baseline.save(saved.path()).unwrap();
+        let mut filter = BaselineMode::Filter(Baseline::load(saved.path()).unwrap());
+
+        // A third copy of the line is one finding more than the baseline excuses
+        let new = "wrod here\nwrod here\nwrod here\n";
+        let reported = filter.apply_to_text(file.path(), new, 1, findings(new, &["wrod"]));
+        assert_eq!(words(&reported), [(3, "wrod")]);
+    }
+
+    #[test]
+    fn chunks_give_the_keys_of_the_whole_file() {
+        let text = "intro\nteh one\nother\nteh two\nWrod three\n";
+        let file = TempFile::new("baseline-chunks.md", text);
+        let all = findings(text, &["teh", "Wrod"]);
+        let whole = baseline_entries(file.path(), &all).unwrap();
+
+        // The chunk starting at line 3, with the findings in it
+        let chunk = text.split_inclusive('\n').skip(2).collect::<String>();
+        let in_chunk = all
+            .iter()
+            .filter(|finding| finding.line >= 3)
+            .cloned()
+            .collect::<Vec<_>>();
+        let chunked = text_baseline_entries(file.path(), &chunk, 3, &in_chunk);
+        assert_eq!(chunked, whole[1..]);
+        assert_eq!(whole[2].word, "wrod");
+        assert_ne!(whole[0].fingerprint, whole[1].fingerprint);
+
+        let mut write = BaselineMode::Write(Baseline::new());
+        write.apply(file.path(), all.clone()).unwrap();
+        let mut filter = BaselineMode::Filter(recorded(write));
+        assert!(filter
+            .apply_to_text(file.path(), &chunk, 3, in_chunk)
+            .is_empty());
+    }
+}
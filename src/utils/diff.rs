@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+/// The lines a unified diff adds to one file.
+#[derive(Debug, Default)]
+pub struct FileChanges {
+    /// Path of the new file, without git's `b/` prefix
+    pub path: String,
+    /// Added lines by their 1-based number in the new file
+    pub added_lines: BTreeMap<usize, String>,
+}
+
+impl FileChanges {
+    /// Whether `source` has every added line at its number, i.e. is the new file.
+    pub fn matches(&self, source: &str) -> bool {
+        let lines = source.lines().collect::<Vec<_>>();
+        self.added_lines
+            .iter()
+            .all(|(&number, line)| lines.get(number - 1) == Some(&line.as_str()))
+    }
+
+    /// The new file as far as the diff shows it: added lines at their numbers, every
+    /// other line left empty.
+    pub fn added_text(&self) -> String {
+        let mut text = String::new();
+        let mut number = 1;
+        for (&line_number, line) in &self.added_lines {
+            while number < line_number {
+                text.push('\n');
+                number += 1;
+            }
+            text.push_str(line);
+            text.push('\n');
+            number += 1;
+        }
+        text
+    }
+}
+
+/// Reads the added lines of each file of a unified diff, such as `git diff` output.
+///
+/// Deleted files and files without added lines are left out; so are binary files,
+/// which have no hunks.
+pub fn parse_unified_diff(diff: &str) -> Vec<FileChanges> {
+    let mut files: Vec<FileChanges> = Vec::new();
+    // Line numbers in the new file, and lines left in the current hunk
+    let mut new_line = 0;
+    let mut old_remaining = 0;
+    let mut new_remaining = 0;
+
+    for line in diff.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if old_remaining == 0 && new_remaining == 0 {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                files.push(FileChanges {
+                    path: new_file_path(path),
+                    ..FileChanges::default()
+                });
+            } else if let Some((start, old_count, new_count)) = parse_hunk_header(line) {
+                new_line = start;
+                old_remaining = old_count;
+                new_remaining = new_count;
+            }
+            continue;
+        }
+
+        match line.chars().next() {
+            Some('+') => {
+                if let Some(file) = files.last_mut() {
+                    file.added_lines.insert(new_line, line[1..].to_string());
+                }
+                new_line += 1;
+                new_remaining = new_remaining.saturating_sub(1);
+            }
+            Some('-') => old_remaining = old_remaining.saturating_sub(1),
+            // "\ No newline at end of file"
+            Some('\\') => {}
+            // Context, which some tools strip of its leading space when empty
+            _ => {
+                new_line += 1;
+                old_remaining = old_remaining.saturating_sub(1);
+                new_remaining = new_remaining.saturating_sub(1);
+            }
+        }
+    }
+
+    files.retain(|file| file.path != "/dev/null" && !file.added_lines.is_empty());
+    files
+}
+
+// First new line number and line counts of `@@ -start,count +start,count @@`; counts
+// default to 1
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let ranges = line.strip_prefix("@@ -")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(" +")?;
+    let (_, old_count) = parse_range(old)?;
+    let (new_start, new_count) = parse_range(new)?;
+    Some((new_start, old_count, new_count))
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+// Drops the timestamp some tools append after a tab, git's quoting and its `b/` prefix
+fn new_file_path(path: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path);
+    let path = match path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    {
+        Some(quoted) => unquote(quoted),
+        None => path.to_string(),
+    };
+    path.strip_prefix("b/").unwrap_or(&path).to_string()
+}
+
+// Undoes git's C-style quoting of paths with special or non-ASCII characters, where
+// `caf\303\251` is `café` written as octal UTF-8 bytes
+fn unquote(quoted: &str) -> String {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut rest = quoted.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let Some((&escape, tail)) = rest.split_first() else {
+            bytes.push(b'\\');
+            break;
+        };
+        rest = tail;
+        // Three octal digits make one byte, up to \377
+        match (escape, rest) {
+            (b'0'..=b'3', [second @ b'0'..=b'7', third @ b'0'..=b'7', tail @ ..]) => {
+                bytes.push((escape - b'0') * 64 + (second - b'0') * 8 + (third - b'0'));
+                rest = tail;
+            }
+            (b'a', _) => bytes.push(0x07),
+            (b'b', _) => bytes.push(0x08),
+            (b't', _) => bytes.push(b'\t'),
+            (b'n', _) => bytes.push(b'\n'),
+            (b'v', _) => bytes.push(0x0b),
+            (b'f', _) => bytes.push(0x0c),
+            (b'r', _) => bytes.push(b'\r'),
+            // `\"` and `\\`
+            _ => bytes.push(escape),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn added(file: &FileChanges) -> Vec<(usize, &str)> {
+        file.added_lines
+            .iter()
+            .map(|(&number, line)| (number, line.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_added_lines_of_every_hunk() {
+        let diff = "\
+diff --git a/notes.md b/notes.md
+index 1111111..2222222 100644
+--- a/notes.md
++++ b/notes.md
+@@ -1,3 +1,4 @@
+ # Notes
+-old line
++new line
++another line
+ kept
+@@ -10,2 +11,3 @@ section heading
+ context
++later line
+ last
+diff --git a/gone.md b/gone.md
+deleted file mode 100644
+--- a/gone.md
++++ /dev/null
+@@ -1 +0,0 @@
+-removed
+diff --git a/new.txt b/new.txt
+new file mode 100644
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
+++++ not a header
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "notes.md");
+        assert_eq!(
+            added(&files[0]),
+            [(2, "new line"), (3, "another line"), (12, "later line")]
+        );
+        assert_eq!(files[1].path, "new.txt");
+        assert_eq!(added(&files[1]), [(1, "+++ not a header")]);
+        assert_eq!(files[1].added_text(), "+++ not a header\n");
+        let new = "# Notes\nnew line\nanother line\nkept\n".to_string() + &"\n".repeat(6);
+        assert!(files[0].matches(&(new + "context\nlater line\nlast\n")));
+        assert!(!files[0].matches("# Notes\nold line\nkept\n"));
+    }
+
+    #[test]
+    fn reads_renames_and_missing_newlines() {
+        let diff = "\
+diff --git a/old name.md b/new name.md
+similarity index 90%
+rename from old name.md
+rename to new name.md
+--- a/old name.md\t2024-01-01 10:00:00
++++ b/new name.md\t2024-01-02 10:00:00
+@@ -1,2 +1,2 @@
+ first
+-second
+\\ No newline at end of file
++second line
+\\ No newline at end of file
+diff --git a/only-renamed.md b/moved.md
+similarity index 100%
+rename from only-renamed.md
+rename to moved.md
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "new name.md");
+        assert_eq!(added(&files[0]), [(2, "second line")]);
+        assert_eq!(files[0].added_text(), "\nsecond line\n");
+    }
+
+    #[test]
+    fn unquotes_git_paths() {
+        let diff = "\
+--- \"a/caf\\303\\251.md\"
++++ \"b/caf\\303\\251.md\"
+@@ -0,0 +1 @@
++caf\u{e9}
+--- \"a/tab\\there \\\"quoted\\\\.md\"
++++ \"b/tab\\there \\\"quoted\\\\.md\"
+@@ -0,0 +1 @@
++x
+";
+        let files = parse_unified_diff(diff);
+        let paths = files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["café.md", "tab\there \"quoted\\.md"]);
+        assert_eq!(new_file_path("plain.md"), "plain.md");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::utils::tokenizer::{positioned_tokens, Token, Tokenizer};
+use crate::utils::unicode_normalization::is_combining_mark;
+use regex::Regex;
+
+/// Splits source code identifiers into their sub-words, so `getUserAccnt` yields
+/// `get`, `User` and `Accnt`, each positioned inside the identifier.
+///
+/// Handles camelCase, PascalCase, snake_case, kebab-case and SCREAMING_CASE; runs of
+/// capitals keep together until the last one starts a new word (`HTTPServer` is
+/// `HTTP` + `Server`). Digits separate words and are never part of one.
+pub struct IdentifierTokenizer {
+    identifier_pattern: Regex,
+}
+
+impl IdentifierTokenizer {
+    pub fn new() -> Self {
+        IdentifierTokenizer {
+            identifier_pattern: Regex::new(r"[\p{L}\p{M}\p{N}_]+(?:-[\p{L}\p{M}\p{N}_]+)*")
+                .unwrap(),
+        }
+    }
+}
+
+impl Default for IdentifierTokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tokenizer for IdentifierTokenizer {
+    fn tokens<'s, 'a: 's>(&'s self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 's> {
+        let sub_words = self
+            .identifier_pattern
+            .find_iter(text)
+            .flat_map(move |mat| {
+                split_identifier(mat.as_str())
+                    .into_iter()
+                    .map(move |(start, end)| {
+                        let (start, end) = (mat.start() + start, mat.start() + end);
+                        (start, &text[start..end])
+                    })
+            });
+        Box::new(positioned_tokens(text, sub_words))
+    }
+}
+
+// Byte ranges of the sub-words of `identifier`
+fn split_identifier(identifier: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = identifier.char_indices().collect();
+    let mut words = Vec::new();
+    let mut word_start: Option<usize> = None;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        // Combining marks belong to the letter before them
+        let continues_word = word_start.is_some() && is_combining_mark(c);
+        if !(c.is_alphabetic() || continues_word) {
+            if let Some(start) = word_start.take() {
+                words.push((start, offset));
+            }
+            continue;
+        }
+
+        // The base letter before, skipping the combining marks attached to it
+        let previous = chars[..i]
+            .iter()
+            .rev()
+            .map(|&(_, c)| c)
+            .find(|&c| !is_combining_mark(c));
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let starts_word = c.is_uppercase()
+            && match previous {
+                Some(previous) if previous.is_lowercase() => true,
+                // Last capital of an acronym followed by lowercase, as in HTTP|Server
+                Some(previous) if previous.is_uppercase() => next.is_some_and(char::is_lowercase),
+                _ => false,
+            };
+
+        match word_start {
+            Some(start) if starts_word => {
+                words.push((start, offset));
+                word_start = Some(offset);
+            }
+            Some(_) => {}
+            None => word_start = Some(offset),
+        }
+    }
+
+    if let Some(start) = word_start {
+        words.push((start, identifier.len()));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<(&str, usize, usize)> {
+        IdentifierTokenizer::new()
+            .tokens(text)
+            .map(|token| (token.text, token.line, token.column))
+            .collect()
+    }
+
+    #[test]
+    fn splits_identifiers() {
+        let cases: &[(&str, &[&str])] = &[
+            ("getUserAccnt", &["get", "User", "Accnt"]),
+            ("PascalCase", &["Pascal", "Case"]),
+            ("snake_case_name", &["snake", "case", "name"]),
+            ("kebab-case-name", &["kebab", "case", "name"]),
+            ("MAX_RETRY_COUNT", &["MAX", "RETRY", "COUNT"]),
+            ("HTTPServer", &["HTTP", "Server"]),
+            ("parseURL2", &["parse", "URL"]),
+            ("parseURL2Str", &["parse", "URL", "Str"]),
+            ("utf8Decoder", &["utf", "Decoder"]),
+            ("__init__", &["init"]),
+            ("trailing-", &["trailing"]),
+            ("-leading", &["leading"]),
+            ("a--b", &["a", "b"]),
+            ("IO", &["IO"]),
+            ("aB", &["a", "B"]),
+            ("x", &["x"]),
+            ("123", &[]),
+            ("naïveÉcole", &["naïve", "École"]),
+            // A combining mark stays with its letter
+            ("cafe\u{301}Menu", &["cafe\u{301}", "Menu"]),
+        ];
+        for (identifier, expected) in cases {
+            let actual: Vec<&str> = words(identifier).iter().map(|word| word.0).collect();
+            assert_eq!(actual, *expected, "{:?}", identifier);
+        }
+    }
+
+    #[test]
+    fn positions_sub_words() {
+        assert_eq!(
+            words("let émailAddr = x;\n  fooBar()"),
+            [
+                ("let", 1, 1),
+                ("émail", 1, 5),
+                ("Addr", 1, 10),
+                ("x", 1, 17),
+                ("foo", 2, 3),
+                ("Bar", 2, 6),
+            ]
+        );
+    }
+}
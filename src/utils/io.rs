@@ -1 +1,381 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, Write};
 
+const TOOL_NAME: &str = "rust-spell-checker";
+const RULE_ID: &str = "spelling/unknown-word";
+
+/// How findings are written out.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// `file:line:col: message`, for editors' quickfix lists
+    Text,
+    /// One JSON object per finding
+    Jsonl,
+    /// SARIF 2.1.0, for code-scanning dashboards
+    Sarif,
+    /// Checkstyle XML, for CI report plugins
+    Checkstyle,
+    Csv,
+}
+
+/// One misspelled word of a checked file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub file: String,
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+    /// Name of the algorithm that made the suggestions
+    pub checker: String,
+}
+
+impl Finding {
+    pub fn message(&self) -> String {
+        if self.suggestions.is_empty() {
+            format!("Unknown word \"{}\"", self.word)
+        } else {
+            format!(
+                "Unknown word \"{}\", did you mean {}?",
+                self.word,
+                self.suggestions.join(", ")
+            )
+        }
+    }
+
+    fn end_column(&self) -> usize {
+        self.column + self.word.chars().count()
+    }
+}
+
+/// Writes the findings of each checked file in one of the `OutputFormat`s.
+///
+/// SARIF is a single document, so its results are held until `finish`; every other
+/// format is written as files are reported. A file may be reported in several parts,
+/// one after the other, as it is checked.
+pub struct Reporter<W: Write> {
+    format: OutputFormat,
+    out: W,
+    sarif_results: Vec<Value>,
+    /// File whose Checkstyle element is still open
+    checkstyle_file: Option<String>,
+}
+
+impl<W: Write> Reporter<W> {
+    pub fn new(format: OutputFormat, mut out: W) -> io::Result<Self> {
+        match format {
+            OutputFormat::Checkstyle => {
+                writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+                writeln!(out, r#"<checkstyle version="4.3">"#)?;
+            }
+            OutputFormat::Csv => writeln!(out, "file,line,column,word,suggestions,checker")?,
+            _ => {}
+        }
+        Ok(Reporter {
+            format,
+            out,
+            sarif_results: Vec::new(),
+            checkstyle_file: None,
+        })
+    }
+
+    /// Reports every finding of `file`, which may have none.
+    pub fn report(&mut self, file: &str, findings: &[Finding]) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Text => {
+                for finding in findings {
+                    writeln!(
+                        self.out,
+                        "{}:{}:{}: {}",
+                        finding.file,
+                        finding.line,
+                        finding.column,
+                        finding.message()
+                    )?;
+                }
+            }
+            OutputFormat::Jsonl => {
+                for finding in findings {
+                    serde_json::to_writer(&mut self.out, finding)?;
+                    writeln!(self.out)?;
+                }
+            }
+            OutputFormat::Sarif => {
+                self.sarif_results.extend(findings.iter().map(sarif_result));
+            }
+            OutputFormat::Checkstyle => {
+                if self.checkstyle_file.as_deref() != Some(file) {
+                    if self.checkstyle_file.take().is_some() {
+                        writeln!(self.out, "  </file>")?;
+                    }
+                    writeln!(self.out, r#"  <file name="{}">"#, escape_xml(file))?;
+                    self.checkstyle_file = Some(file.to_string());
+                }
+                for finding in findings {
+                    writeln!(
+                        self.out,
+                        r#"    <error line="{}" column="{}" severity="warning" message="{}" source="{}"/>"#,
+                        finding.line,
+                        finding.column,
+                        escape_xml(&finding.message()),
+                        escape_xml(&format!("{}.{}", TOOL_NAME, finding.checker))
+                    )?;
+                }
+            }
+            OutputFormat::Csv => {
+                for finding in findings {
+                    writeln!(
+                        self.out,
+                        "{},{},{},{},{},{}",
+                        escape_csv(&finding.file),
+                        finding.line,
+                        finding.column,
+                        escape_csv(&finding.word),
+                        escape_csv(&finding.suggestions.join(";")),
+                        escape_csv(&finding.checker)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the document of the formats that need it and flushes the output.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.format {
+            OutputFormat::Sarif => {
+                let log = sarif_log(std::mem::take(&mut self.sarif_results));
+                serde_json::to_writer_pretty(&mut self.out, &log)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Checkstyle => {
+                if self.checkstyle_file.take().is_some() {
+                    writeln!(self.out, "  </file>")?;
+                }
+                writeln!(self.out, "</checkstyle>")?;
+            }
+            _ => {}
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn sarif_log(results: Vec<Value>) -> Value {
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": RULE_ID,
+                        "name": "UnknownWord",
+                        "shortDescription": { "text": "Word not found in the dictionary" },
+                        "defaultConfiguration": { "level": "warning" }
+                    }]
+                }
+            },
+            // Columns are counted in characters, not the UTF-16 units SARIF assumes
+            "columnKind": "unicodeCodePoints",
+            "results": results
+        }]
+    })
+}
+
+fn sarif_result(finding: &Finding) -> Value {
+    json!({
+        "ruleId": RULE_ID,
+        "ruleIndex": 0,
+        "level": "warning",
+        "message": { "text": finding.message() },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": path_to_uri(&finding.file) },
+                "region": {
+                    "startLine": finding.line,
+                    "startColumn": finding.column,
+                    "endColumn": finding.end_column()
+                }
+            }
+        }],
+        "properties": {
+            "word": finding.word,
+            "suggestions": finding.suggestions,
+            "checker": finding.checker
+        }
+    })
+}
+
+// Relative paths stay relative to the checkout, as code-scanning uploads expect
+fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    let escaped: String = path
+        .chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '?' => "%3F".to_string(),
+            '%' => "%25".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    if escaped.starts_with('/') {
+        format!("file://{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Quotes a field as in RFC 4180 when it holds a separator, quote or line break
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(file: &str, line: usize, word: &str, suggestions: &[&str]) -> Finding {
+        Finding {
+            file: file.to_string(),
+            line,
+            column: 3,
+            word: word.to_string(),
+            suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+            checker: "levenshtein".to_string(),
+        }
+    }
+
+    // Output of reporting each file's findings in turn
+    fn report(format: OutputFormat, files: &[(&str, Vec<Finding>)]) -> String {
+        let mut reporter = Reporter::new(format, Vec::new()).unwrap();
+        for (file, findings) in files {
+            reporter.report(file, findings).unwrap();
+        }
+        String::from_utf8(reporter.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn writes_text() {
+        let findings = vec![
+            finding("a.md", 1, "teh", &["the", "ten"]),
+            finding("a.md", 2, "zzxq", &[]),
+        ];
+        assert_eq!(
+            report(OutputFormat::Text, &[("a.md", findings)]),
+            "a.md:1:3: Unknown word \"teh\", did you mean the, ten?\n\
+             a.md:2:3: Unknown word \"zzxq\"\n"
+        );
+    }
+
+    #[test]
+    fn writes_jsonl() {
+        let output = report(
+            OutputFormat::Jsonl,
+            &[("a.md", vec![finding("a.md", 1, "teh", &["the"])])],
+        );
+        let value: Value = serde_json::from_str(output.trim_end()).unwrap();
+        assert_eq!(value["word"], "teh");
+        assert_eq!(value["suggestions"], json!(["the"]));
+    }
+
+    #[test]
+    fn escapes_csv_fields() {
+        let findings = vec![finding("dir, \"x\"/a.md", 1, "it\"s", &["its", "it's"])];
+        assert_eq!(
+            report(OutputFormat::Csv, &[("dir, \"x\"/a.md", findings)]),
+            "file,line,column,word,suggestions,checker\n\
+             \"dir, \"\"x\"\"/a.md\",1,3,\"it\"\"s\",its;it's,levenshtein\n"
+        );
+        assert_eq!(escape_csv("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn writes_checkstyle_with_one_element_per_file() {
+        let output = report(
+            OutputFormat::Checkstyle,
+            &[
+                ("a<&>.md", vec![finding("a<&>.md", 1, "teh", &["the"])]),
+                ("a<&>.md", vec![finding("a<&>.md", 9, "d'oh", &[])]),
+                ("b.md", vec![]),
+            ],
+        );
+        assert_eq!(
+            output,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="a&lt;&amp;&gt;.md">
+    <error line="1" column="3" severity="warning" message="Unknown word &quot;teh&quot;, did you mean the?" source="rust-spell-checker.levenshtein"/>
+    <error line="9" column="3" severity="warning" message="Unknown word &quot;d&apos;oh&quot;" source="rust-spell-checker.levenshtein"/>
+  </file>
+  <file name="b.md">
+  </file>
+</checkstyle>
+"#
+        );
+    }
+
+    #[test]
+    fn writes_sarif() {
+        let findings = vec![finding("docs/my notes.md", 4, "naïve", &["naive"])];
+        let output = report(OutputFormat::Sarif, &[("docs/my notes.md", findings)]);
+        let log: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            log["$schema"],
+            "https://json.schemastore.org/sarif-2.1.0.json"
+        );
+
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], TOOL_NAME);
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], RULE_ID);
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], RULE_ID);
+        assert_eq!(result["level"], "warning");
+        assert_eq!(
+            result["message"]["text"],
+            "Unknown word \"naïve\", did you mean naive?"
+        );
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "docs/my%20notes.md");
+        assert_eq!(
+            location["region"],
+            json!({ "startLine": 4, "startColumn": 3, "endColumn": 8 })
+        );
+    }
+
+    #[test]
+    fn converts_paths_to_uris() {
+        assert_eq!(path_to_uri("./a/b#1?.md"), "a/b%231%3F.md");
+        assert_eq!(path_to_uri("/tmp/100%.md"), "file:///tmp/100%25.md");
+        assert_eq!(path_to_uri(r"dir\file.md"), "dir/file.md");
+    }
+}
@@ -1,16 +1,80 @@
+use crate::utils::unicode_normalization::nfc;
+use log::warn;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, Write};
 
 pub fn load_dictionary(file_path: &str) -> io::Result<HashSet<String>> {
-    let file = File::open(file_path)?;
-    let reader = io::BufReader::new(file);
+    let lines = read_dictionary_lines(file_path)?;
 
-    let dictionary = reader
-        .lines()
-        .filter_map(Result::ok)
-        .map(|word| word.to_lowercase())
+    if !lines.invalid_lines.is_empty() {
+        warn!(
+            "Skipped {} dictionary lines with invalid UTF-8 in {} (first at line {})",
+            lines.invalid_lines.len(),
+            file_path,
+            lines.invalid_lines[0]
+        );
+    }
+
+    let dictionary = lines
+        .entries
+        .into_iter()
+        .map(|(_, word)| nfc(&word).to_lowercase())
         .collect::<HashSet<String>>();
 
     Ok(dictionary)
 }
+
+/// Raw dictionary file contents, before lowercasing or deduplication.
+pub struct DictionaryLines {
+    /// 1-based line number and the entry exactly as written
+    pub entries: Vec<(usize, String)>,
+    /// 1-based line numbers that are not valid UTF-8
+    pub invalid_lines: Vec<usize>,
+}
+
+pub fn read_dictionary_lines(file_path: &str) -> io::Result<DictionaryLines> {
+    let file = File::open(file_path)?;
+    let reader = io::BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut invalid_lines = Vec::new();
+
+    for (line_number, bytes) in reader.split(b'\n').enumerate() {
+        let mut bytes = bytes?;
+        if bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(word) if word.is_empty() => {}
+            Ok(word) => entries.push((line_number + 1, word)),
+            Err(_) => invalid_lines.push(line_number + 1),
+        }
+    }
+
+    Ok(DictionaryLines {
+        entries,
+        invalid_lines,
+    })
+}
+
+/// Adds `words` at the end of a word list, creating it if needed.
+pub fn append_words(file_path: &str, words: &[String]) -> io::Result<()> {
+    if words.is_empty() {
+        return Ok(());
+    }
+    // A last line without its line break would be joined with the first new word
+    let unterminated =
+        fs::read(file_path).is_ok_and(|bytes| bytes.last().is_some_and(|&b| b != b'\n'));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    if unterminated {
+        writeln!(file)?;
+    }
+    for word in words {
+        writeln!(file, "{}", word)?;
+    }
+    Ok(())
+}
@@ -1,5 +1,154 @@
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Bytes read per chunk when streaming a dataset, rounded up to the next line end.
+pub const DEFAULT_CHUNK_SIZE: usize = 4 << 20;
 
 pub fn read_dataset(file_path: &str) -> Result<String, std::io::Error> {
     fs::read_to_string(file_path)
 }
+
+/// A run of whole lines of a dataset file.
+pub struct DatasetChunk {
+    pub text: String,
+    /// Byte offset of `text` in the file
+    pub offset: usize,
+    /// 1-based line number of the first line of `text`
+    pub line: usize,
+}
+
+/// Reads a dataset file chunk by chunk, so only the chunks being processed are in memory.
+pub struct DatasetChunks {
+    reader: BufReader<File>,
+    chunk_size: usize,
+    offset: usize,
+    line: usize,
+}
+
+pub fn read_dataset_chunks(file_path: &str, chunk_size: usize) -> io::Result<DatasetChunks> {
+    Ok(DatasetChunks {
+        reader: BufReader::new(File::open(file_path)?),
+        chunk_size,
+        offset: 0,
+        line: 1,
+    })
+}
+
+impl DatasetChunks {
+    fn read_chunk(&mut self) -> io::Result<Option<DatasetChunk>> {
+        let mut bytes = Vec::with_capacity(self.chunk_size);
+        (&mut self.reader)
+            .take(self.chunk_size as u64)
+            .read_to_end(&mut bytes)?;
+        // Finish the last line, so that no word or multibyte char is split between chunks
+        if bytes.last().is_some_and(|&b| b != b'\n') {
+            self.reader.read_until(b'\n', &mut bytes)?;
+        }
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+
+        let text =
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let chunk = DatasetChunk {
+            offset: self.offset,
+            line: self.line,
+            text,
+        };
+        self.offset += chunk.text.len();
+        self.line += chunk.text.matches('\n').count();
+        Ok(Some(chunk))
+    }
+}
+
+impl Iterator for DatasetChunks {
+    type Item = io::Result<DatasetChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_chunk().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every chunking of `contents` for chunk sizes from 1 to past its length
+    fn chunkings(name: &str, contents: &[u8]) -> Vec<Vec<DatasetChunk>> {
+        let file_path =
+            std::env::temp_dir().join(format!("chunks-{}-{}.txt", name, std::process::id()));
+        fs::write(&file_path, contents).unwrap();
+        let chunkings = (1..=contents.len() + 1)
+            .map(|chunk_size| {
+                read_dataset_chunks(file_path.to_str().unwrap(), chunk_size)
+                    .unwrap()
+                    .collect::<io::Result<Vec<_>>>()
+                    .unwrap()
+            })
+            .collect();
+        fs::remove_file(&file_path).unwrap();
+        chunkings
+    }
+
+    fn check_chunks(name: &str, contents: &str) {
+        for chunks in chunkings(name, contents.as_bytes()) {
+            let mut offset = 0;
+            let mut line = 1;
+            for (i, chunk) in chunks.iter().enumerate() {
+                assert_eq!(chunk.offset, offset);
+                assert_eq!(chunk.line, line);
+                assert!(chunk.text.ends_with('\n') || i == chunks.len() - 1);
+                offset += chunk.text.len();
+                line += chunk.text.matches('\n').count();
+            }
+            let joined: String = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+            assert_eq!(joined, contents);
+        }
+    }
+
+    #[test]
+    fn splits_at_line_ends() {
+        check_chunks("lines", "one\ntwo words\n\nthree\n");
+        assert_eq!(
+            chunkings("sizes", b"ab\ncd\nef\n")[3]
+                .iter()
+                .map(|chunk| (chunk.text.as_str(), chunk.line))
+                .collect::<Vec<_>>(),
+            [("ab\ncd\n", 1), ("ef\n", 3)]
+        );
+    }
+
+    #[test]
+    fn never_splits_multibyte_chars() {
+        check_chunks("utf8", "café\nnaïve 日本語\n🦀\n");
+    }
+
+    #[test]
+    fn never_splits_crlf() {
+        check_chunks("crlf", "one\r\ntwo\r\n\r\nthree\r\n");
+    }
+
+    #[test]
+    fn keeps_a_last_line_without_newline() {
+        check_chunks("unterminated", "one\ntwo\nlast");
+        let last = chunkings("unterminated-last", b"one\ntwo\nlast")[0]
+            .pop()
+            .unwrap();
+        assert_eq!((last.text.as_str(), last.offset, last.line), ("last", 8, 3));
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let file_path =
+            std::env::temp_dir().join(format!("chunks-invalid-{}.txt", std::process::id()));
+        fs::write(&file_path, b"ok\n\xff\n").unwrap();
+        let chunks: Vec<_> = read_dataset_chunks(file_path.to_str().unwrap(), 3)
+            .unwrap()
+            .collect();
+        fs::remove_file(&file_path).unwrap();
+        assert!(chunks[0].is_ok());
+        assert!(chunks[1]
+            .as_ref()
+            .is_err_and(|e| e.kind() == io::ErrorKind::InvalidData));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::utils::tokenizer::Token;
+use crate::utils::unicode_normalization::nfc;
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Which kinds of non-word text are skipped before checking.
+#[derive(Debug, Clone)]
+pub struct TokenFilterOptions {
+    pub urls: bool,
+    pub emails: bool,
+    pub paths: bool,
+    pub hashes: bool,
+    pub versions: bool,
+    /// Any token containing a digit, which covers numbers with units such as `10ms` or `4GB`
+    pub numbers: bool,
+    pub acronyms: bool,
+}
+
+impl Default for TokenFilterOptions {
+    fn default() -> Self {
+        TokenFilterOptions {
+            urls: true,
+            emails: true,
+            paths: true,
+            hashes: true,
+            versions: true,
+            numbers: true,
+            acronyms: true,
+        }
+    }
+}
+
+/// Drops tokens that are part of URLs, emails, paths and other non-word text.
+///
+/// Most of these span several tokens (`https://example.com/a` tokenizes into four words),
+/// so they are matched against the raw text and every token overlapping a match is dropped.
+pub struct TokenFilter {
+    options: TokenFilterOptions,
+    span_patterns: Vec<Regex>,
+    // Project words that are never reported, normalized and lowercased
+    ignored_words: HashSet<String>,
+}
+
+impl TokenFilter {
+    pub fn new(options: TokenFilterOptions) -> Self {
+        let mut patterns = Vec::new();
+        if options.urls {
+            patterns.push(r#"\b(?:[a-zA-Z][a-zA-Z0-9+.-]*://|www\.)[^\s<>"'`()\[\]]+"#);
+        }
+        if options.emails {
+            patterns.push(r"\b[\w.+-]+@[\w-]+(?:\.[\w-]+)+\b");
+        }
+        if options.paths {
+            // Absolute, home-relative and dot-relative unix paths, not preceded by a word
+            // so that "and/or" stays prose; relative paths need two separators or a file
+            // extension, which "input/output" has neither of; and Windows drive paths
+            patterns.push(r"(?m)(?:^|[^\w./~-])(?:~|\.{1,2})?/[\w.-]+(?:/[\w.-]+)*/?");
+            patterns.push(r"\b[\w.-]+(?:/[\w.-]+){2,}/?");
+            patterns.push(r"\b[\w.-]+/(?:[\w.-]+/)*[\w-]+\.[a-zA-Z][a-zA-Z0-9]{0,7}\b");
+            patterns.push(r"\b[a-zA-Z]:\\[^\s]+");
+        }
+        if options.hashes {
+            patterns.push(
+                r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+            );
+            patterns.push(r"\b0[xX][0-9a-fA-F]+\b");
+        }
+        if options.versions {
+            patterns.push(r"\bv?\d+(?:\.\d+)+(?:[-+][\w.]+)?\b");
+        }
+
+        TokenFilter {
+            span_patterns: patterns
+                .into_iter()
+                .map(|pattern| Regex::new(pattern).unwrap())
+                .collect(),
+            options,
+            ignored_words: HashSet::new(),
+        }
+    }
+
+    /// Words accepted as they are, whatever the dictionary says.
+    pub fn with_ignored_words(mut self, words: impl IntoIterator<Item = String>) -> Self {
+        self.ignored_words
+            .extend(words.into_iter().map(|word| nfc(&word).to_lowercase()));
+        self
+    }
+
+    /// Extra regexes matched against the text; every token overlapping a match is dropped.
+    pub fn with_ignore_patterns(mut self, patterns: &[String]) -> Result<Self, regex::Error> {
+        for pattern in patterns {
+            self.span_patterns.push(Regex::new(pattern)?);
+        }
+        Ok(self)
+    }
+
+    /// Lazily drops the non-word `tokens` of `text`, which must come in text order.
+    pub fn filter<'f, 'a: 'f>(
+        &'f self,
+        text: &'f str,
+        tokens: impl Iterator<Item = Token<'a>> + 'f,
+    ) -> impl Iterator<Item = Token<'a>> + 'f {
+        let mut spans = self.skipped_spans(text).into_iter().peekable();
+
+        // Tokens and spans are both sorted by start offset
+        tokens.filter(move |token| {
+            while spans
+                .peek()
+                .is_some_and(|span| span.end <= token.range.start)
+            {
+                spans.next();
+            }
+            let in_span = spans
+                .peek()
+                .is_some_and(|span| span.start < token.range.end);
+            !in_span && !self.is_non_word(text, token)
+        })
+    }
+
+    fn skipped_spans(&self, text: &str) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = self
+            .span_patterns
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(text).map(|mat| mat.range()))
+            .collect();
+        spans.sort_by_key(|span| span.start);
+
+        // Merge overlapping spans so the filter can walk them in one pass
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+
+    fn is_non_word(&self, text: &str, token: &Token) -> bool {
+        let word = token.text;
+        if !self.ignored_words.is_empty() && self.ignored_words.contains(&nfc(word).to_lowercase())
+        {
+            return true;
+        }
+        if self.options.numbers && word.chars().any(|c| c.is_ascii_digit()) {
+            return true;
+        }
+        if self.options.hashes && is_hex_hash(word) {
+            return true;
+        }
+        self.options.acronyms && is_acronym(word) && !is_screaming_case_part(text, token)
+    }
+}
+
+// Commit hashes and digests: long hex strings mixing letters and digits
+fn is_hex_hash(word: &str) -> bool {
+    word.len() >= 7
+        && word.chars().all(|c| c.is_ascii_hexdigit())
+        && word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+// `MAX` in `MAX_RETRIES` is a word of a SCREAMING_CASE identifier, not an acronym
+fn is_screaming_case_part(text: &str, token: &Token) -> bool {
+    text[..token.range.start].ends_with('_') || text[token.range.end..].starts_with('_')
+}
+
+// Plurals such as `APIs` count too
+fn is_acronym(word: &str) -> bool {
+    let word = word.strip_suffix('s').unwrap_or(word);
+    word.chars().filter(|c| c.is_alphabetic()).count() >= 2
+        && word
+            .chars()
+            .all(|c| c.is_uppercase() || c.is_ascii_digit() || c == '\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tokenizer::{RegexTokenizer, Tokenizer};
+
+    fn kept_words(filter: &TokenFilter, text: &str) -> Vec<String> {
+        let tokenizer = RegexTokenizer::words();
+        filter
+            .filter(text, tokenizer.tokens(text))
+            .map(|token| token.text.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn skips_non_word_text() {
+        let filter = TokenFilter::new(TokenFilterOptions::default());
+        let cases: &[(&str, &[&str])] = &[
+            ("see https://exmple.com/pth?q=wrd now", &["see", "now"]),
+            ("at www.exmple.org today", &["at", "today"]),
+            ("(ftp://host/fle) ok", &["ok"]),
+            ("mail jhon.doe+tag@exmple.co.uk back", &["mail", "back"]),
+            ("open /usr/lcal/bin please", &["open", "please"]),
+            ("edit ~/.cnfig and ./scrpt.sh", &["edit", "and"]),
+            ("see src/utls/tokenzer.rs here", &["see", "here"]),
+            ("in srce/utls/dir too", &["in", "too"]),
+            (r"run C:\Prgram\Fles now", &["run", "now"]),
+            // Prose with a single slash is kept
+            ("and/or input/output", &["and", "or", "input", "output"]),
+            ("commit 3f2a9c1d fixed", &["commit", "fixed"]),
+            ("id 123e4567-e89b-12d3-a456-426614174000 ok", &["id", "ok"]),
+            ("mask 0xFFab set", &["mask", "set"]),
+            ("since v1.2.3-beta.1 and 2.0", &["since", "and"]),
+            ("wait 10ms for 4GB", &["wait", "for"]),
+            ("the HTTP and JSON APIs", &["the", "and"]),
+            // Words of SCREAMING_CASE identifiers are still checked
+            ("MAX_RETRES set", &["MAX_RETRES", "set"]),
+            // A single capital and hex words without digits are not acronyms or hashes
+            ("I cafe deadbeefcafe", &["I", "cafe", "deadbeefcafe"]),
+            ("Is it", &["Is", "it"]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(kept_words(&filter, text), *expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn skips_hashes_without_the_number_rule() {
+        let filter = TokenFilter::new(TokenFilterOptions {
+            numbers: false,
+            ..TokenFilterOptions::default()
+        });
+        assert_eq!(
+            kept_words(&filter, "commit 3f2a9c1d and a1b2c3 deadbeefcafe"),
+            ["commit", "and", "a1b2c3", "deadbeefcafe"]
+        );
+    }
+
+    #[test]
+    fn keeps_disabled_kinds() {
+        let options = TokenFilterOptions {
+            urls: false,
+            emails: false,
+            paths: false,
+            hashes: false,
+            versions: false,
+            numbers: false,
+            acronyms: false,
+        };
+        let filter = TokenFilter::new(options);
+        assert_eq!(
+            kept_words(&filter, "HTTP www.exmple.org deadbeef1"),
+            ["HTTP", "www", "exmple", "org", "deadbeef1"]
+        );
+    }
+
+    #[test]
+    fn skips_ignored_words_and_patterns() {
+        let filter = TokenFilter::new(TokenFilterOptions::default())
+            .with_ignored_words(["Rustacean".to_string(), "cafe\u{301}".to_string()])
+            .with_ignore_patterns(&[r"TODO\(\w+\)".to_string()])
+            .unwrap();
+        assert_eq!(
+            kept_words(&filter, "rustacean café TODO(bob) fine"),
+            ["fine"]
+        );
+        assert!(TokenFilter::new(TokenFilterOptions::default())
+            .with_ignore_patterns(&["(".to_string()])
+            .is_err());
+    }
+}
@@ -1,90 +1,147 @@
-// pub fn tokenizer(text: &str) -> Vec<String> {
-//     text.split_whitespace()
-//         .map(|word| word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase())
-//         .filter(|word| !word.is_empty())
-//         .collect()
-// }
-
-// use unicode_segmentation::UnicodeSegmentation;
-//
-// pub fn tokenizer(text: &str) -> Vec<String> {
-//     text.unicode_words() // Using unicode-segmentation for better word boundaries
-//         .map(|word| word.to_lowercase()) // Convert each word to lowercase
-//         .collect()
-// }
-
-// use regex::Regex;
-//
-// pub fn tokenizer(text: &str) -> Vec<String> {
-//     let token_pattern = Regex::new(r"[a-zA-Z0-9]+('[a-z]+)?").unwrap();
-//     token_pattern
-//         .find_iter(text)
-//         .map(|mat| mat.as_str().to_lowercase())
-//         .collect()
-// }
-
-// use regex::Regex;
-//
-// pub fn tokenizer(text: &str) -> Vec<String> {
-//     let token_pattern = Regex::new(r"[a-zA-Z0-9]+('[a-zA-Z0-9]+)?").unwrap();
-//     token_pattern
-//         .find_iter(text)
-//         .map(|mat| handle_contractions(mat.as_str()).to_lowercase())
-//         .collect()
-// }
-//
-// fn handle_contractions(word: &str) -> String {
-//     match word.to_lowercase().as_str() {
-//         "ve" => "have",
-//         "n't" => "not",
-//         "'re" => "are",
-//         "'ll" => "will",
-//         "'m" => "am",
-//         "'d" => "would",
-//         "can't" => "cannot",
-//         "won't" => "will not",
-//         "don't" => "do not",
-//         "doesn't" => "does not",
-//         "didn't" => "did not",
-//         "aren't" => "are not",
-//         "isn't" => "is not",
-//         "wasn't" => "was not",
-//         "weren't" => "were not",
-//         "haven't" => "have not",
-//         "hasn't" => "has not",
-//         "hadn't" => "had not",
-//         "wouldn't" => "would not",
-//         "shouldn't" => "should not",
-//         "couldn't" => "could not",
-//         "mightn't" => "might not",
-//         "mustn't" => "must not",
-//         "i'm" => "i am",
-//         "you're" => "you are",
-//         "he's" => "he is",
-//         "she's" => "she is",
-//         "it's" => "it is",
-//         "we're" => "we are",
-//         "they're" => "they are",
-//         "i've" => "i have",
-//         "you've" => "you have",
-//         "we've" => "we have",
-//         "they've" => "they have",
-//         "i'd" => "i would",
-//         "you'd" => "you would",
-//         "he'd" => "he would",
-//         "she'd" => "she would",
-//         "we'd" => "we would",
-//         "they'd" => "they would",
-//         _ => word,
-//     }.to_string()
-// }
-
+use crate::utils::identifier_tokenizer::IdentifierTokenizer;
+use crate::utils::unicode_normalization::is_combining_mark;
 use regex::Regex;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const TOKENIZER_NAMES: &[&str] = &[
+    "regex",
+    "unicode",
+    "whitespace",
+    "alphanumeric",
+    "identifier",
+];
+
+/// A word as it appears in the input, with its position.
+#[derive(Debug, Clone)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    /// Byte range of `text` within the input
+    pub range: Range<usize>,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+}
+
+/// Start offsets of every line in a text, for turning byte offsets into line/column.
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { line_starts }
+    }
+
+    /// 1-based line and character column of the byte `offset` in `text`.
+    pub fn position(&self, text: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        (line, text[line_start..offset].chars().count() + 1)
+    }
+}
+
+/// Splits text into positioned words; implementations differ in what counts as a word.
+pub trait Tokenizer: Sync {
+    /// Lazily yields the words of `text` in order, borrowed from it.
+    fn tokens<'s, 'a: 's>(&'s self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 's>;
+}
+
+/// Looks up a tokenizer strategy by name, see `TOKENIZER_NAMES`.
+pub fn tokenizer_by_name(name: &str) -> Option<Box<dyn Tokenizer>> {
+    match name {
+        "regex" => Some(Box::new(RegexTokenizer::words())),
+        "unicode" => Some(Box::new(UnicodeWordTokenizer)),
+        "whitespace" => Some(Box::new(WhitespaceTokenizer)),
+        "alphanumeric" => Some(Box::new(RegexTokenizer::alphanumeric())),
+        "identifier" => Some(Box::new(IdentifierTokenizer::new())),
+        _ => None,
+    }
+}
+
+/// Whitespace-separated chunks with surrounding non-letters trimmed off.
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokens<'s, 'a: 's>(&'s self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 's> {
+        let words = text
+            .split(char::is_whitespace)
+            .map(|chunk| chunk.trim_matches(|c: char| !c.is_alphabetic() && !is_combining_mark(c)))
+            .filter(|word| !word.is_empty())
+            .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word));
+        Box::new(positioned_tokens(text, words))
+    }
+}
+
+/// Unicode word boundaries (UAX #29), the best fit for prose.
+pub struct UnicodeWordTokenizer;
+
+impl Tokenizer for UnicodeWordTokenizer {
+    fn tokens<'s, 'a: 's>(&'s self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 's> {
+        Box::new(positioned_tokens(text, text.unicode_word_indices()))
+    }
+}
+
+pub struct RegexTokenizer {
+    pattern: Regex,
+}
+
+impl RegexTokenizer {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(RegexTokenizer {
+            pattern: Regex::new(pattern)?,
+        })
+    }
+
+    /// Runs of word characters and apostrophes, straight or curly.
+    pub fn words() -> Self {
+        Self::new(r"\b[\w'’]+\b").unwrap()
+    }
+
+    /// ASCII letters and digits with an optional apostrophe suffix, stricter for code.
+    pub fn alphanumeric() -> Self {
+        Self::new(r"[a-zA-Z0-9]+('[a-zA-Z0-9]+)?").unwrap()
+    }
+}
+
+impl Tokenizer for RegexTokenizer {
+    fn tokens<'s, 'a: 's>(&'s self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 's> {
+        let matches = self
+            .pattern
+            .find_iter(text)
+            .map(|mat| (mat.start(), mat.as_str()));
+        Box::new(positioned_tokens(text, matches))
+    }
+}
 
-pub fn tokenizer(text: &str) -> Vec<String> {
-    let token_pattern = Regex::new(r"\b[\w']+\b").unwrap();
-    token_pattern
-        .find_iter(text)
-        .map(|mat| mat.as_str().to_lowercase())
-        .collect()
+/// Positions words given with their start offset in `text`. Offsets must not decrease,
+/// so that line and column are tracked incrementally instead of indexing every line.
+pub fn positioned_tokens<'a>(
+    text: &'a str,
+    words: impl Iterator<Item = (usize, &'a str)>,
+) -> impl Iterator<Item = Token<'a>> {
+    let mut offset = 0;
+    let mut line = 1;
+    let mut column = 1;
+    words.map(move |(start, word)| {
+        for c in text[offset..start].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        offset = start;
+        Token {
+            text: word,
+            range: start..start + word.len(),
+            line,
+            column,
+        }
+    })
 }
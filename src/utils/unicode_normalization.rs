@@ -0,0 +1,30 @@
+use std::borrow::Cow;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+pub use unicode_normalization::char::{compose, is_combining_mark};
+
+/// `text` in Unicode Normalization Form C, so that "é" typed as `e` + U+0301 matches
+/// the precomposed "é" of the dictionary.
+pub fn nfc(text: &str) -> Cow<'_, str> {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(text),
+        _ => Cow::Owned(text.nfc().collect()),
+    }
+}
+
+/// `word` with accents and other diacritics removed, "résumé" becoming "resume".
+///
+/// Only marks that attach to a letter are dropped; spacing vowel signs, which have no
+/// combining class, are part of the word.
+pub fn fold_accents(word: &str) -> Cow<'_, str> {
+    if word.is_ascii() {
+        return Cow::Borrowed(word);
+    }
+    Cow::Owned(
+        word.nfd()
+            .filter(|&c| canonical_combining_class(c) == 0)
+            .nfc()
+            .collect(),
+    )
+}